@@ -0,0 +1,51 @@
+#![cfg_attr(
+    all(not(debug_assertions), target_os = "windows"),
+    windows_subsystem = "windows"
+)]
+
+use dioxus_query::prelude::*;
+use std::time::Duration;
+use tokio::time::sleep;
+
+use dioxus::prelude::*;
+
+fn main() {
+    launch(app);
+}
+
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct GetUserName;
+
+impl QueryCapability for GetUserName {
+    type Ok = String;
+    type Err = ();
+    type Keys = usize;
+
+    async fn run(&self, user_id: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        sleep(Duration::from_millis(650)).await;
+        Ok(format!("User {user_id}"))
+    }
+}
+
+#[allow(non_snake_case)]
+#[component]
+fn User(id: usize) -> Element {
+    let user_name = use_query(Query::new(id, GetUserName));
+
+    rsx!(
+        p { "{user_name.read().state():?}" }
+    )
+}
+
+fn app() -> Element {
+    let client = use_query_client();
+
+    // Show the keys and values of these queries in the devtools
+    use_hook(|| client.devtools_debug::<GetUserName>());
+
+    rsx!(
+        User { id: 0 }
+        User { id: 1 }
+        QueryDevtools {}
+    )
+}
@@ -0,0 +1,69 @@
+#![cfg_attr(
+    all(not(debug_assertions), target_os = "windows"),
+    windows_subsystem = "windows"
+)]
+
+use dioxus_query::prelude::*;
+use std::time::Duration;
+use tokio::time::sleep;
+
+use dioxus::prelude::*;
+
+fn main() {
+    launch(app);
+}
+
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct GetPosts;
+
+impl InfiniteQueryCapability for GetPosts {
+    type Page = Vec<String>;
+    type Err = ();
+    type Keys = ();
+    type PageParam = usize;
+
+    fn initial_page_param(&self, _keys: &Self::Keys) -> Self::PageParam {
+        0
+    }
+
+    async fn run_page(
+        &self,
+        _keys: &Self::Keys,
+        page: &Self::PageParam,
+    ) -> Result<Self::Page, Self::Err> {
+        println!("Fetching page {page}");
+        sleep(Duration::from_millis(650)).await;
+        Ok((0..5).map(|i| format!("Post {}", page * 5 + i)).collect())
+    }
+
+    fn next_page_param(
+        &self,
+        _last_page: &Self::Page,
+        last_page_param: &Self::PageParam,
+    ) -> Option<Self::PageParam> {
+        (*last_page_param < 4).then_some(last_page_param + 1)
+    }
+}
+
+fn app() -> Element {
+    let posts = use_infinite_query(Query::new((), Infinite(GetPosts)));
+
+    let load_more = move |_| posts.fetch_next_page();
+    let refresh = move |_| posts.query().invalidate();
+
+    let reader = posts.read();
+    let state = reader.state();
+
+    rsx!(
+        if let Some(data) = state.ok() {
+            for post in data.pages().flatten() {
+                p { "{post}" }
+            }
+        }
+        if posts.is_fetching_next_page() {
+            p { "Loading more..." }
+        }
+        button { disabled: !posts.has_next_page(), onclick: load_more, label { "Load more" } }
+        button { onclick: refresh, label { "Refresh" } }
+    )
+}
@@ -37,7 +37,8 @@ fn User(id: usize) -> Element {
     let user_name = use_query(
         Query::new(id, GetUserName)
             .stale_time(Duration::MAX)
-            .interval_time(Duration::from_secs(2)),
+            .interval_time(Duration::from_secs(2))
+            .structural_sharing(true),
     );
 
     println!("Rendering user {id}");
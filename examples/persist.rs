@@ -0,0 +1,44 @@
+#![cfg_attr(
+    all(not(debug_assertions), target_os = "windows"),
+    windows_subsystem = "windows"
+)]
+
+use dioxus_query::prelude::*;
+use std::time::Duration;
+use tokio::time::sleep;
+
+use dioxus::prelude::*;
+
+fn main() {
+    launch(app);
+}
+
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct GetUserName;
+
+impl QueryCapability for GetUserName {
+    type Ok = String;
+    type Err = ();
+    type Keys = usize;
+
+    async fn run(&self, user_id: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        println!("Fetching name of user {user_id}");
+        sleep(Duration::from_millis(650)).await;
+        Ok(format!("User {user_id}"))
+    }
+}
+
+fn app() -> Element {
+    // Restore the last known names when the app starts
+    use_persist_query_client(|| {
+        PersistQueryClient::new(JsonFilePersister::new("query-cache.json"))
+            .buster("v1")
+            .persist::<GetUserName>()
+    });
+
+    let user_name = use_query(Query::new(0, GetUserName).stale_time(Duration::from_secs(30)));
+
+    rsx!(
+        p { "{user_name.read().state():?}" }
+    )
+}
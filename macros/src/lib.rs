@@ -0,0 +1,69 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields, GenericParam, Index};
+
+/// Derive `QueryKey` for a struct or an enum.
+///
+/// A struct is a single segment of its own type made of a segment per field, in order.
+/// An enum is a single segment made of the whole value, so it needs to be `Clone + PartialEq`.
+/// Either way the type needs to be `'static`.
+#[proc_macro_derive(QueryKey)]
+pub fn derive_query_key(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let fields = match &data.fields {
+                Fields::Named(fields) => fields
+                    .named
+                    .iter()
+                    .map(|field| {
+                        let name = &field.ident;
+                        quote!(self.#name)
+                    })
+                    .collect::<Vec<_>>(),
+                Fields::Unnamed(fields) => (0..fields.unnamed.len())
+                    .map(|i| {
+                        let index = Index::from(i);
+                        quote!(self.#index)
+                    })
+                    .collect::<Vec<_>>(),
+                Fields::Unit => Vec::new(),
+            };
+            quote! {
+                ::std::vec![::dioxus_query::key::KeySegment::fields::<Self>(::std::vec![
+                    #(::dioxus_query::key::KeySegment::of(&#fields)),*
+                ])]
+            }
+        }
+        Data::Enum(_) => quote! {
+            ::std::vec![::dioxus_query::key::KeySegment::value(::std::clone::Clone::clone(self))]
+        },
+        Data::Union(_) => {
+            return syn::Error::new_spanned(&input.ident, "QueryKey can't be derived for unions")
+                .to_compile_error()
+                .into();
+        }
+    };
+
+    for param in input.generics.params.iter_mut() {
+        if let GenericParam::Type(param) = param {
+            param
+                .bounds
+                .push(parse_quote!(::dioxus_query::key::QueryKey));
+            param.bounds.push(parse_quote!('static));
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ::dioxus_query::key::QueryKey for #name #ty_generics #where_clause {
+            fn segments(&self) -> ::std::vec::Vec<::dioxus_query::key::KeySegment> {
+                #body
+            }
+        }
+    }
+    .into()
+}
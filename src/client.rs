@@ -0,0 +1,316 @@
+use std::{any::TypeId, collections::HashMap, future::Future, pin::Pin, rc::Rc, sync::Arc};
+
+use dioxus::prelude::*;
+use dioxus::signals::CopyValue;
+use dioxus_core::provide_root_context;
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use tokio::sync::Notify;
+
+use crate::query::{QueriesStorage, QueryCapability, Tag};
+
+/// Operations of a [QueriesStorage] that don't depend on its query type.
+pub(crate) trait AnyQueriesStorage {
+    fn invalidate_all(&self) -> Pin<Box<dyn Future<Output = ()>>>;
+
+    fn invalidate_tags(&self, tags: Rc<[Tag]>) -> Pin<Box<dyn Future<Output = ()>>>;
+
+    fn clear(&self);
+
+    fn reset(&self) -> Pin<Box<dyn Future<Output = ()>>>;
+
+    fn refetch_on_focus(&self) -> Pin<Box<dyn Future<Output = ()>>>;
+
+    fn refetch_on_reconnect(&self) -> Pin<Box<dyn Future<Output = ()>>>;
+
+    fn is_fetching(&self) -> bool;
+
+    #[cfg(feature = "testing")]
+    fn busy_fetches(&self) -> Vec<Arc<Notify>>;
+
+    #[cfg(feature = "devtools")]
+    fn entries(&self) -> Vec<crate::devtools::QueryEntry>;
+
+    #[cfg(feature = "devtools")]
+    fn invalidate_entry(&self, id: usize) -> Pin<Box<dyn Future<Output = ()>>>;
+
+    #[cfg(feature = "devtools")]
+    fn reset_entry(&self, id: usize) -> Pin<Box<dyn Future<Output = ()>>>;
+
+    #[cfg(feature = "devtools")]
+    fn remove_entry(&self, id: usize);
+}
+
+impl<Q: QueryCapability> AnyQueriesStorage for QueriesStorage<Q> {
+    fn invalidate_all(&self) -> Pin<Box<dyn Future<Output = ()>>> {
+        Box::pin(QueriesStorage::<Q>::invalidate_all())
+    }
+
+    fn invalidate_tags(&self, tags: Rc<[Tag]>) -> Pin<Box<dyn Future<Output = ()>>> {
+        Box::pin(async move { QueriesStorage::<Q>::invalidate_tags(&tags).await })
+    }
+
+    fn clear(&self) {
+        QueriesStorage::clear(self)
+    }
+
+    fn reset(&self) -> Pin<Box<dyn Future<Output = ()>>> {
+        let storage = *self;
+        Box::pin(async move { storage.reset().await })
+    }
+
+    fn refetch_on_focus(&self) -> Pin<Box<dyn Future<Output = ()>>> {
+        let storage = *self;
+        Box::pin(async move { storage.refetch_on_focus().await })
+    }
+
+    fn refetch_on_reconnect(&self) -> Pin<Box<dyn Future<Output = ()>>> {
+        let storage = *self;
+        Box::pin(async move { storage.refetch_on_reconnect().await })
+    }
+
+    fn is_fetching(&self) -> bool {
+        QueriesStorage::is_fetching(self)
+    }
+
+    #[cfg(feature = "testing")]
+    fn busy_fetches(&self) -> Vec<Arc<Notify>> {
+        QueriesStorage::busy_fetches(self)
+    }
+
+    #[cfg(feature = "devtools")]
+    fn entries(&self) -> Vec<crate::devtools::QueryEntry> {
+        QueriesStorage::entries(self)
+    }
+
+    #[cfg(feature = "devtools")]
+    fn invalidate_entry(&self, id: usize) -> Pin<Box<dyn Future<Output = ()>>> {
+        let storage = *self;
+        Box::pin(async move { storage.invalidate_entry(id).await })
+    }
+
+    #[cfg(feature = "devtools")]
+    fn reset_entry(&self, id: usize) -> Pin<Box<dyn Future<Output = ()>>> {
+        let storage = *self;
+        Box::pin(async move { storage.reset_entry(id).await })
+    }
+
+    #[cfg(feature = "devtools")]
+    fn remove_entry(&self, id: usize) {
+        QueriesStorage::remove_entry(self, id)
+    }
+}
+
+/// Manage the queries of every type at once, e.g to wipe all the cached data when logging out.
+///
+/// Every [QueriesStorage] registers itself in the client when created.
+/// Use [QueryClient::storage] to access the queries of a specific type.
+#[derive(Clone, Copy)]
+pub struct QueryClient {
+    storages: CopyValue<HashMap<TypeId, Rc<dyn AnyQueriesStorage>>>,
+    /// Incremented whenever the cached data of a query changes, see [QueryClient::mark_changed].
+    version: CopyValue<u64>,
+    changes: CopyValue<Arc<Notify>>,
+    /// How many mutations are running, see [QueryClient::is_busy].
+    #[cfg(feature = "testing")]
+    running_mutations: CopyValue<usize>,
+    #[cfg(feature = "testing")]
+    activity: CopyValue<Arc<Notify>>,
+}
+
+impl QueryClient {
+    /// Get the [QueryClient] of this app.
+    pub fn current() -> Self {
+        match try_consume_context::<QueryClient>() {
+            Some(client) => client,
+            None => provide_root_context(QueryClient {
+                storages: CopyValue::new_in_scope(HashMap::default(), ScopeId::ROOT),
+                version: CopyValue::new_in_scope(0, ScopeId::ROOT),
+                changes: CopyValue::new_in_scope(Arc::default(), ScopeId::ROOT),
+                #[cfg(feature = "testing")]
+                running_mutations: CopyValue::new_in_scope(0, ScopeId::ROOT),
+                #[cfg(feature = "testing")]
+                activity: CopyValue::new_in_scope(Arc::default(), ScopeId::ROOT),
+            }),
+        }
+    }
+
+    pub(crate) fn register<Q: QueryCapability>(&self, storage: QueriesStorage<Q>) {
+        let mut storages = self.storages;
+        storages.write().insert(TypeId::of::<Q>(), Rc::new(storage));
+    }
+
+    #[cfg(feature = "devtools")]
+    pub(crate) fn storage_of(&self, type_id: TypeId) -> Option<Rc<dyn AnyQueriesStorage>> {
+        self.storages.peek().get(&type_id).cloned()
+    }
+
+    /// Get the [QueriesStorage] of the given query type.
+    pub fn storage<Q: QueryCapability>(&self) -> QueriesStorage<Q> {
+        QueriesStorage::current()
+    }
+
+    pub(crate) fn storages(&self) -> Vec<Rc<dyn AnyQueriesStorage>> {
+        self.storages.peek().values().cloned().collect()
+    }
+
+    /// Invalidate all the queries of every type and await their results.
+    pub async fn invalidate_all(&self) {
+        self.storages()
+            .iter()
+            .map(|storage| storage.invalidate_all())
+            .collect::<FuturesUnordered<_>>()
+            .count()
+            .await;
+    }
+
+    /// Invalidate the queries of every type that have any of the given tags and await their results.
+    ///
+    /// See [QueryCapability::tags].
+    pub async fn invalidate_tags(&self, tags: &[impl AsRef<str>]) {
+        let tags = tags
+            .iter()
+            .map(|tag| Tag::from(tag.as_ref().to_string()))
+            .collect::<Rc<[Tag]>>();
+        self.storages()
+            .iter()
+            .map(|storage| storage.invalidate_tags(tags.clone()))
+            .collect::<FuturesUnordered<_>>()
+            .count()
+            .await;
+    }
+
+    /// Remove the cached queries of every type that are not in use, see [QueriesStorage::clear].
+    pub fn clear(&self) {
+        for storage in self.storages() {
+            storage.clear();
+        }
+    }
+
+    /// Reset all the cached queries of every type, see [QueriesStorage::reset].
+    pub async fn reset(&self) {
+        self.storages()
+            .iter()
+            .map(|storage| storage.reset())
+            .collect::<FuturesUnordered<_>>()
+            .count()
+            .await;
+    }
+
+    /// Rerun the queries of every type that should be refetched when the app regains focus.
+    pub(crate) async fn refetch_on_focus(&self) {
+        self.storages()
+            .iter()
+            .map(|storage| storage.refetch_on_focus())
+            .collect::<FuturesUnordered<_>>()
+            .count()
+            .await;
+    }
+
+    /// Rerun the queries of every type that should be refetched when the app goes back online.
+    pub(crate) async fn refetch_on_reconnect(&self) {
+        self.storages()
+            .iter()
+            .map(|storage| storage.refetch_on_reconnect())
+            .collect::<FuturesUnordered<_>>()
+            .count()
+            .await;
+    }
+
+    /// Let those waiting for changes know that the cached data of a query was settled, written or removed.
+    pub(crate) fn mark_changed(&self) {
+        let mut version = self.version;
+        *version.write() += 1;
+        self.changes.peek().notify_waiters();
+    }
+
+    /// How many times the cached data has changed, see [QueryClient::changed].
+    #[cfg(feature = "persist")]
+    pub(crate) fn version(&self) -> u64 {
+        *self.version.peek()
+    }
+
+    /// Wait until the cached data changes after the given [QueryClient::version].
+    #[cfg(feature = "persist")]
+    pub(crate) async fn changed(&self, since: u64) {
+        let changes = self.changes.peek().clone();
+        loop {
+            // Created before checking the version so no change is missed
+            let notified = changes.notified();
+            if self.version() != since {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Check if any query of any type is running.
+    pub fn is_fetching(&self) -> bool {
+        self.storages().iter().any(|storage| storage.is_fetching())
+    }
+
+    /// Check if any mutation is running, or any query of any type is running and not just waiting for the clock or to be online.
+    #[cfg(feature = "testing")]
+    pub(crate) fn is_busy(&self) -> bool {
+        *self.running_mutations.peek() > 0
+            || self
+                .storages()
+                .iter()
+                .any(|storage| !storage.busy_fetches().is_empty())
+    }
+
+    /// Wait until a running query or mutation finishes, or a query starts waiting for the clock or to be online.
+    #[cfg(feature = "testing")]
+    pub(crate) async fn busy_changed(&self) {
+        let activity = self.activity.peek().clone();
+        let fetches = self
+            .storages()
+            .iter()
+            .flat_map(|storage| storage.busy_fetches())
+            .collect::<Vec<_>>();
+        let mut notified = fetches
+            .iter()
+            .map(|notifier| notifier.notified())
+            .collect::<FuturesUnordered<_>>();
+        notified.push(activity.notified());
+        notified.next().await;
+    }
+
+    /// Let [QueryClient::busy_changed] know that something other than a fetch finishing changed.
+    #[cfg(feature = "testing")]
+    pub(crate) fn notify_activity(&self) {
+        self.activity.peek().notify_waiters();
+    }
+
+    /// Count a mutation as running until the returned guard is dropped.
+    #[cfg(feature = "testing")]
+    pub(crate) fn track_mutation(&self) -> RunningMutation {
+        let mut running_mutations = self.running_mutations;
+        *running_mutations.write() += 1;
+        RunningMutation { client: *self }
+    }
+}
+
+/// A running mutation, see [QueryClient::track_mutation].
+#[cfg(feature = "testing")]
+pub(crate) struct RunningMutation {
+    client: QueryClient,
+}
+
+#[cfg(feature = "testing")]
+impl Drop for RunningMutation {
+    fn drop(&mut self) {
+        // The app might have been dropped meanwhile
+        let mut running_mutations = self.client.running_mutations;
+        if let Ok(mut running_mutations) = running_mutations.try_write() {
+            *running_mutations -= 1;
+        };
+        if let Ok(activity) = self.client.activity.try_peek() {
+            activity.notify_waiters();
+        };
+    }
+}
+
+/// Get the [QueryClient] to manage the queries of every type at once.
+pub fn use_query_client() -> QueryClient {
+    use_hook(QueryClient::current)
+}
@@ -0,0 +1,140 @@
+use std::{cell::Cell, future::Future, pin::Pin, rc::Rc, sync::Arc, time::Duration};
+
+use dioxus::prelude::*;
+use dioxus_core::provide_root_context;
+use tokio::sync::Notify;
+
+#[cfg(not(target_family = "wasm"))]
+use tokio::time;
+#[cfg(not(target_family = "wasm"))]
+use tokio::time::Instant;
+#[cfg(target_family = "wasm")]
+use wasmtimer::tokio as time;
+#[cfg(target_family = "wasm")]
+use web_time::Instant;
+
+/// Source of time of the queries and mutations, used for their stale, clean and interval times,
+/// and the retry backoffs.
+///
+/// Defaults to [SystemClock], tests can use a [MockClock] instead, see [QueryClock::provide].
+pub trait Clock {
+    /// The current instant.
+    fn now(&self) -> Instant;
+
+    /// Wait for the given duration.
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()>>>;
+}
+
+/// [Clock] that follows the real time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()>>> {
+        Box::pin(time::sleep(duration))
+    }
+}
+
+/// [Clock] that only moves forward when [MockClock::advance] is called,
+/// so tests of the stale, clean and interval times don't need to wait for real.
+///
+/// Clones share the same time.
+#[derive(Clone)]
+pub struct MockClock {
+    start: Instant,
+    elapsed: Rc<Cell<Duration>>,
+    notifier: Arc<Notify>,
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Rc::default(),
+            notifier: Arc::default(),
+        }
+    }
+
+    /// How much time has been advanced since this clock was created.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed.get()
+    }
+
+    /// Move the time forward, waking up the sleeps that are due.
+    ///
+    /// Each sleep wakes up once, e.g advancing by three intervals only ticks an interval task once.
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed.set(self.elapsed.get() + duration);
+        self.notifier.notify_waiters();
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed.get()
+    }
+
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()>>> {
+        let clock = self.clone();
+        let deadline = self.now() + duration;
+        Box::pin(async move {
+            loop {
+                let notified = clock.notifier.clone().notified_owned();
+                if clock.now() >= deadline {
+                    return;
+                }
+                notified.await;
+            }
+        })
+    }
+}
+
+/// The [Clock] of this app.
+#[derive(Clone)]
+pub struct QueryClock {
+    clock: Rc<dyn Clock>,
+}
+
+impl QueryClock {
+    /// Get the [QueryClock] of this app, a [SystemClock] unless another one was provided.
+    pub fn current() -> Self {
+        match try_consume_context::<QueryClock>() {
+            Some(clock) => clock,
+            None => Self::provide(SystemClock),
+        }
+    }
+
+    /// Use the given [Clock] in this app.
+    ///
+    /// Call it at the root of the app, before any query or mutation is used.
+    pub fn provide(clock: impl Clock + 'static) -> Self {
+        provide_root_context(QueryClock {
+            clock: Rc::new(clock),
+        })
+    }
+
+    /// The current instant.
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    /// Wait for the given duration.
+    pub fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()>>> {
+        self.clock.sleep(duration)
+    }
+}
+
+/// Get the [QueryClock] of this app.
+pub fn use_query_clock() -> QueryClock {
+    use_hook(QueryClock::current)
+}
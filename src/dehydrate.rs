@@ -0,0 +1,210 @@
+use std::{any::type_name, collections::BTreeMap, rc::Rc, time::Duration};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    clock::QueryClock,
+    query::{QueriesStorage, QueryCapability},
+};
+#[cfg(not(target_family = "wasm"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(not(target_family = "wasm"))]
+use tokio::time::Instant;
+#[cfg(target_family = "wasm")]
+use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// A serialized settled query.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct DehydratedQuery {
+    pub keys: Value,
+    pub result: Result<Value, Value>,
+    /// When was the query settled, in milliseconds since the Unix epoch.
+    pub updated_at: u64,
+}
+
+/// A serializable snapshot of the settled queries of some query types, see [QueryHydration].
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct DehydratedState {
+    /// The settled queries of every query type, by type name.
+    pub queries: BTreeMap<String, Vec<DehydratedQuery>>,
+}
+
+type HydrateQueries = Box<dyn Fn(&[DehydratedQuery], &HydrateOptions)>;
+type DehydrateQueries = Box<dyn Fn(&WallClock) -> Vec<DehydratedQuery>>;
+
+struct HydratedType {
+    name: String,
+    hydrate: HydrateQueries,
+    dehydrate: DehydrateQueries,
+}
+
+pub(crate) struct HydrateOptions {
+    /// Ignore the queries older than this.
+    pub(crate) max_age: Option<Duration>,
+    /// Skip the fetch of the hydrated queries mounted in the current render.
+    pub(crate) skip_mount: bool,
+}
+
+/// The query types to move between a [DehydratedState] and their [QueriesStorage].
+///
+/// For server-side rendering, [QueryHydration::dehydrate] the settled queries on the server,
+/// embed the [DehydratedState] in the page and [QueryHydration::hydrate] it on the client before
+/// the queries are used. The hydrated queries start as settled, and those mounted in the same render are not fetched again.
+/// Those mounted later are fetched as usual if they are stale.
+///
+/// Query types are identified by their type name, so both sides need to register the same types.
+#[derive(Clone, Default)]
+pub struct QueryHydration {
+    types: Vec<Rc<HydratedType>>,
+}
+
+impl QueryHydration {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Include the queries of the given type.
+    pub fn query<Q: QueryCapability>(mut self) -> Self
+    where
+        Q::Keys: Serialize + DeserializeOwned,
+        Q::Ok: Serialize + DeserializeOwned,
+        Q::Err: Serialize + DeserializeOwned,
+    {
+        self.types.push(Rc::new(HydratedType {
+            name: type_name::<Q>().to_string(),
+            hydrate: Box::new(|queries, options| {
+                let storage = QueriesStorage::<Q>::current();
+                let clock = QueryClock::current();
+                for query in queries {
+                    let age = Duration::from_millis(now().saturating_sub(query.updated_at));
+                    if options.max_age.is_some_and(|max_age| age > max_age) {
+                        continue;
+                    }
+                    let Some(settlement_instant) = clock.now().checked_sub(age) else {
+                        continue;
+                    };
+                    let Ok(keys) = serde_json::from_value(query.keys.clone()) else {
+                        continue;
+                    };
+                    let res = match &query.result {
+                        Ok(value) => serde_json::from_value(value.clone()).map(Ok),
+                        Err(value) => serde_json::from_value(value.clone()).map(Err),
+                    };
+                    if let Ok(res) = res {
+                        storage.seed(keys, res, settlement_instant, options.skip_mount);
+                    }
+                }
+                if options.skip_mount {
+                    storage.end_hydration();
+                }
+            }),
+            dehydrate: Box::new(|wall_clock| {
+                let storage = QueriesStorage::<Q>::current();
+                let mut queries = Vec::<DehydratedQuery>::new();
+                storage.for_each_settled(|keys, res, settlement_instant| {
+                    let Ok(keys) = serde_json::to_value(keys) else {
+                        return;
+                    };
+                    let result = match res {
+                        Ok(value) => serde_json::to_value(value).map(Ok),
+                        Err(value) => serde_json::to_value(value).map(Err),
+                    };
+                    let Ok(result) = result else {
+                        return;
+                    };
+                    let updated_at = wall_clock.at(settlement_instant);
+
+                    // Keep only the latest result of those queries with the same keys
+                    match queries.iter_mut().find(|query| query.keys == keys) {
+                        Some(query) if query.updated_at >= updated_at => {}
+                        Some(query) => {
+                            query.result = result;
+                            query.updated_at = updated_at;
+                        }
+                        None => queries.push(DehydratedQuery {
+                            keys,
+                            result,
+                            updated_at,
+                        }),
+                    }
+                });
+                queries
+            }),
+        }));
+        self
+    }
+
+    /// Snapshot the settled queries of the registered types.
+    pub fn dehydrate(&self) -> DehydratedState {
+        self.dehydrate_with(&WallClock::new())
+    }
+
+    /// Populate the [QueriesStorage] of the registered types with the given snapshot.
+    ///
+    /// Call it before the queries are used, e.g at the root of the app.
+    pub fn hydrate(&self, state: &DehydratedState) {
+        self.hydrate_with(
+            state,
+            &HydrateOptions {
+                max_age: None,
+                skip_mount: true,
+            },
+        )
+    }
+
+    pub(crate) fn dehydrate_with(&self, wall_clock: &WallClock) -> DehydratedState {
+        let queries = self
+            .types
+            .iter()
+            .map(|hydrated_type| {
+                (
+                    hydrated_type.name.clone(),
+                    (hydrated_type.dehydrate)(wall_clock),
+                )
+            })
+            .collect();
+        DehydratedState { queries }
+    }
+
+    pub(crate) fn hydrate_with(&self, state: &DehydratedState, options: &HydrateOptions) {
+        for hydrated_type in &self.types {
+            if let Some(queries) = state.queries.get(&hydrated_type.name) {
+                (hydrated_type.hydrate)(queries, options);
+            }
+        }
+    }
+}
+
+/// Converts [Instant]s to milliseconds since the Unix epoch,
+/// always giving the same result for the same [Instant].
+pub(crate) struct WallClock {
+    instant: Instant,
+    timestamp: u64,
+}
+
+impl WallClock {
+    pub(crate) fn new() -> Self {
+        Self {
+            instant: QueryClock::current().now(),
+            timestamp: now(),
+        }
+    }
+
+    fn at(&self, instant: Instant) -> u64 {
+        if instant >= self.instant {
+            self.timestamp + (instant - self.instant).as_millis() as u64
+        } else {
+            self.timestamp
+                .saturating_sub((self.instant - instant).as_millis() as u64)
+        }
+    }
+}
+
+/// Milliseconds since the Unix epoch.
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
@@ -0,0 +1,200 @@
+use std::{any::TypeId, fmt::Debug, time::Duration};
+
+use dioxus::prelude::*;
+
+use crate::{
+    client::{use_query_client, QueryClient},
+    query::{QueryCapability, QueryStateData},
+};
+#[cfg(not(target_family = "wasm"))]
+use tokio::time;
+#[cfg(target_family = "wasm")]
+use wasmtimer::tokio as time;
+
+/// Formatters of the keys and state of a query type, see [QueryClient::devtools_debug].
+pub(crate) struct DebugQuery<Q: QueryCapability> {
+    pub(crate) keys: fn(&Q::Keys) -> String,
+    pub(crate) state: fn(&QueryStateData<Q>) -> String,
+}
+
+impl<Q: QueryCapability> Clone for DebugQuery<Q> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Q: QueryCapability> Copy for DebugQuery<Q> {}
+
+/// Description of a cached query, as listed by [QueryDevtools].
+#[derive(Clone, PartialEq, Debug)]
+pub struct QueryEntry {
+    /// Identifies the entry while it's cached.
+    pub id: usize,
+    pub type_id: TypeId,
+    pub type_name: &'static str,
+    /// The [Debug] formatted keys if the type was registered with [QueryClient::devtools_debug],
+    /// otherwise their hash.
+    pub keys: String,
+    /// The [Debug] formatted state if the type was registered with [QueryClient::devtools_debug],
+    /// otherwise its name.
+    pub state: String,
+    pub subscribers: usize,
+    /// How long ago was the query last settled.
+    pub settled_ago: Option<Duration>,
+    pub is_fetching: bool,
+    pub has_interval_task: bool,
+    pub has_clean_task: bool,
+}
+
+impl QueryClient {
+    /// Show the keys and state of the queries of the given type with their [Debug] formatting in the [QueryDevtools].
+    pub fn devtools_debug<Q: QueryCapability>(&self)
+    where
+        Q::Keys: Debug,
+        Q::Ok: Debug,
+        Q::Err: Debug,
+    {
+        self.storage::<Q>().set_debug(DebugQuery {
+            keys: |keys| format!("{keys:?}"),
+            state: |state| format!("{state:?}"),
+        });
+    }
+
+    /// Describe the cached queries of every type.
+    pub fn entries(&self) -> Vec<QueryEntry> {
+        let mut entries = self
+            .storages()
+            .iter()
+            .flat_map(|storage| storage.entries())
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.type_name.cmp(b.type_name).then(a.keys.cmp(&b.keys)));
+        entries
+    }
+
+    /// Invalidate the given cached query and await its result.
+    pub async fn invalidate_entry(&self, entry: &QueryEntry) {
+        if let Some(storage) = self.storage_of(entry.type_id) {
+            storage.invalidate_entry(entry.id).await
+        }
+    }
+
+    /// Reset the given cached query and await its re-execution if it's in use.
+    pub async fn reset_entry(&self, entry: &QueryEntry) {
+        if let Some(storage) = self.storage_of(entry.type_id) {
+            storage.reset_entry(entry.id).await
+        }
+    }
+
+    /// Remove the given cached query, unless it still has subscribers.
+    pub fn remove_entry(&self, entry: &QueryEntry) {
+        if let Some(storage) = self.storage_of(entry.type_id) {
+            storage.remove_entry(entry.id)
+        }
+    }
+}
+
+/// Lists every cached query across types, with buttons to invalidate, reset or remove them.
+///
+/// Keys and states are opaque unless their type is registered with [QueryClient::devtools_debug].
+#[component]
+pub fn QueryDevtools(
+    /// How often to refresh the list. Defaults to `500ms`.
+    #[props(default = Duration::from_millis(500))]
+    refresh_interval: Duration,
+) -> Element {
+    let client = use_query_client();
+    let mut refresh = use_signal(|| 0u64);
+
+    use_future(move || async move {
+        loop {
+            time::sleep(refresh_interval).await;
+            refresh += 1;
+        }
+    });
+
+    // Subscribe to the refreshes
+    refresh.read();
+    let entries = client.entries();
+
+    rsx!(
+        div {
+            class: "dioxus-query-devtools",
+            style: "font-family: monospace; font-size: 12px; overflow: auto;",
+            p { "{entries.len()} cached queries" }
+            table {
+                thead {
+                    tr {
+                        th { "Type" }
+                        th { "Keys" }
+                        th { "State" }
+                        th { "Subscribers" }
+                        th { "Settled" }
+                        th { "Tasks" }
+                        th {}
+                    }
+                }
+                tbody {
+                    for entry in entries {
+                        QueryDevtoolsEntry { key: "{entry.type_name}-{entry.id}", entry }
+                    }
+                }
+            }
+        }
+    )
+}
+
+#[component]
+fn QueryDevtoolsEntry(entry: QueryEntry) -> Element {
+    let client = use_query_client();
+
+    let settled = match entry.settled_ago {
+        Some(ago) => format!("{:.1}s ago", ago.as_secs_f32()),
+        None => "Never".to_string(),
+    };
+    let mut tasks = Vec::new();
+    if entry.is_fetching {
+        tasks.push("fetching");
+    }
+    if entry.has_interval_task {
+        tasks.push("interval");
+    }
+    if entry.has_clean_task {
+        tasks.push("clean");
+    }
+    let tasks = tasks.join(", ");
+
+    let invalidate = {
+        let entry = entry.clone();
+        move |_| {
+            let entry = entry.clone();
+            spawn(async move { client.invalidate_entry(&entry).await });
+        }
+    };
+    let reset = {
+        let entry = entry.clone();
+        move |_| {
+            let entry = entry.clone();
+            spawn(async move { client.reset_entry(&entry).await });
+        }
+    };
+    let remove = {
+        let entry = entry.clone();
+        move |_| client.remove_entry(&entry)
+    };
+
+    rsx!(
+        tr {
+            td { "{entry.type_name}" }
+            td { "{entry.keys}" }
+            td { "{entry.state}" }
+            td { "{entry.subscribers}" }
+            td { "{settled}" }
+            td { "{tasks}" }
+            td {
+                button { onclick: invalidate, "Invalidate" }
+                button { onclick: reset, "Reset" }
+                button { onclick: remove, "Remove" }
+            }
+        }
+    )
+}
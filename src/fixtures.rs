@@ -0,0 +1,282 @@
+use std::{
+    any::type_name,
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fs,
+    marker::PhantomData,
+    path::PathBuf,
+    rc::Rc,
+    time::Duration,
+};
+
+use dioxus::prelude::*;
+use dioxus_core::spawn_forever;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use tokio::sync::Notify;
+
+use crate::{
+    clock::QueryClock,
+    query::{QueriesStorage, QueryCapability, QueryInterceptor},
+};
+
+/// A recorded result of a query.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct QueryFixture {
+    pub keys: Value,
+    pub result: Result<Value, Value>,
+}
+
+/// The recorded results of some query types, in the order they settled, see [QueryFixtures].
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct FixtureFile {
+    /// The results of every query type, by type name.
+    pub queries: BTreeMap<String, Vec<QueryFixture>>,
+}
+
+/// Whether to record the results of the queries or to replay them, see [QueryFixtures].
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum FixtureMode {
+    /// Write the final result of every execution to the fixture file, replacing its previous content.
+    /// The file is written [QueryFixtures::throttle_time] after new results are recorded, once the app is dropped or on [UseQueryFixtures::flush].
+    Record,
+    /// Serve the results from the fixture file instead of running the queries.
+    Replay,
+}
+
+type RegisterFixtures = Box<dyn FnOnce(&Rc<FixturesState>)>;
+
+/// Record the results of the selected query types to a JSON file, and replay them later instead of running the queries,
+/// e.g for offline demos, screenshot tests or to reproduce bug reports locally. Use it with [use_query_fixtures].
+///
+/// When replaying, the results of the queries with the same keys are served in the order they were recorded,
+/// repeating the last one once exhausted. Queries without recorded results run as usual.
+///
+/// Query types are identified by their type name, so both sides need to register the same types.
+pub struct QueryFixtures {
+    path: PathBuf,
+    mode: FixtureMode,
+    throttle_time: Duration,
+    types: Vec<RegisterFixtures>,
+}
+
+impl QueryFixtures {
+    pub fn new(path: impl Into<PathBuf>, mode: FixtureMode) -> Self {
+        Self {
+            path: path.into(),
+            mode,
+            throttle_time: Duration::from_secs(1),
+            types: Vec::new(),
+        }
+    }
+
+    /// Record the results to the given file, see [FixtureMode::Record].
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self::new(path, FixtureMode::Record)
+    }
+
+    /// Replay the results from the given file, see [FixtureMode::Replay].
+    pub fn replay(path: impl Into<PathBuf>) -> Self {
+        Self::new(path, FixtureMode::Replay)
+    }
+
+    /// How long to wait after a result is recorded before writing the file, so the results recorded meanwhile are written at once.
+    /// Defaults to `1s`.
+    pub fn throttle_time(self, throttle_time: Duration) -> Self {
+        Self {
+            throttle_time,
+            ..self
+        }
+    }
+
+    /// Record or replay the queries of the given type.
+    pub fn query<Q: QueryCapability>(mut self) -> Self
+    where
+        Q::Keys: Serialize,
+        Q::Ok: Serialize + DeserializeOwned,
+        Q::Err: Serialize + DeserializeOwned,
+    {
+        self.types.push(Box::new(|state| {
+            QueriesStorage::<Q>::current().intercept(FixtureInterceptor::<Q> {
+                state: state.clone(),
+                query: PhantomData,
+            });
+        }));
+        self
+    }
+
+    /// Load the fixture file if replaying, and start recording or replaying the registered types.
+    fn mount(self) -> Rc<FixturesState> {
+        // A missing or invalid file is treated as empty
+        let file = match self.mode {
+            FixtureMode::Record => FixtureFile::default(),
+            FixtureMode::Replay => fs::read(&self.path)
+                .ok()
+                .and_then(|content| serde_json::from_slice(&content).ok())
+                .unwrap_or_default(),
+        };
+        let state = Rc::new(FixturesState {
+            path: self.path,
+            mode: self.mode,
+            file: RefCell::new(file),
+            served: RefCell::default(),
+            unsaved: Cell::new(false),
+            recorded: Notify::new(),
+        });
+        for register in self.types {
+            register(&state);
+        }
+
+        // Write the file whenever new results are recorded
+        if self.mode == FixtureMode::Record {
+            let state = state.clone();
+            let throttle_time = self.throttle_time;
+            spawn_forever(async move {
+                let clock = QueryClock::current();
+                loop {
+                    state.recorded.notified().await;
+
+                    // Write the results recorded meanwhile at once
+                    clock.sleep(throttle_time).await;
+                    state.save();
+                }
+            });
+        }
+        state
+    }
+}
+
+struct FixturesState {
+    path: PathBuf,
+    mode: FixtureMode,
+    file: RefCell<FixtureFile>,
+    /// How many results have been replayed, by type name and keys.
+    served: RefCell<Vec<(&'static str, Value, usize)>>,
+    /// Whether there are recorded results that are not saved yet.
+    unsaved: Cell<bool>,
+    /// Notified whenever a result is recorded.
+    recorded: Notify,
+}
+
+impl FixturesState {
+    /// Add the given result, see [FixturesState::save].
+    fn record(&self, name: &'static str, fixture: QueryFixture) {
+        self.file
+            .borrow_mut()
+            .queries
+            .entry(name.to_string())
+            .or_default()
+            .push(fixture);
+        self.unsaved.set(true);
+        self.recorded.notify_one();
+    }
+
+    /// Write the recorded results to the file, if there are new ones. IO errors are ignored.
+    fn save(&self) {
+        if !self.unsaved.replace(false) {
+            return;
+        }
+        if let Ok(content) = serde_json::to_vec_pretty(&*self.file.borrow()) {
+            let _ = fs::write(&self.path, content);
+        }
+    }
+
+    /// Get the next result of the queries with the given keys, if any was recorded.
+    fn replay(&self, name: &'static str, keys: &Value) -> Option<Result<Value, Value>> {
+        let file = self.file.borrow();
+        let fixtures = file
+            .queries
+            .get(name)?
+            .iter()
+            .filter(|fixture| fixture.keys == *keys)
+            .collect::<Vec<_>>();
+        let last = fixtures.len().checked_sub(1)?;
+
+        let mut served = self.served.borrow_mut();
+        let i = match served
+            .iter_mut()
+            .find(|(served_name, served_keys, _)| *served_name == name && served_keys == keys)
+        {
+            Some((_, _, count)) => {
+                *count += 1;
+                *count
+            }
+            None => {
+                served.push((name, keys.clone(), 0));
+                0
+            }
+        };
+        Some(fixtures[i.min(last)].result.clone())
+    }
+}
+
+impl Drop for FixturesState {
+    fn drop(&mut self) {
+        self.save();
+    }
+}
+
+struct FixtureInterceptor<Q: QueryCapability> {
+    state: Rc<FixturesState>,
+    query: PhantomData<Q>,
+}
+
+impl<Q: QueryCapability> QueryInterceptor<Q> for FixtureInterceptor<Q>
+where
+    Q::Keys: Serialize,
+    Q::Ok: Serialize + DeserializeOwned,
+    Q::Err: Serialize + DeserializeOwned,
+{
+    fn intercept(&self, keys: &Q::Keys) -> Option<Result<Q::Ok, Q::Err>> {
+        if self.state.mode != FixtureMode::Replay {
+            return None;
+        }
+        let keys = serde_json::to_value(keys).ok()?;
+        match self.state.replay(type_name::<Q>(), &keys)? {
+            Ok(value) => serde_json::from_value(value).ok().map(Ok),
+            Err(value) => serde_json::from_value(value).ok().map(Err),
+        }
+    }
+
+    fn settled(&self, keys: &Q::Keys, res: &Result<Q::Ok, Q::Err>) {
+        if self.state.mode != FixtureMode::Record {
+            return;
+        }
+        let Ok(keys) = serde_json::to_value(keys) else {
+            return;
+        };
+        let result = match res {
+            Ok(value) => serde_json::to_value(value).map(Ok),
+            Err(value) => serde_json::to_value(value).map(Err),
+        };
+        if let Ok(result) = result {
+            self.state
+                .record(type_name::<Q>(), QueryFixture { keys, result });
+        }
+    }
+}
+
+/// Handle of the [QueryFixtures] of the app, see [use_query_fixtures].
+#[derive(Clone)]
+pub struct UseQueryFixtures {
+    state: Rc<FixturesState>,
+}
+
+impl UseQueryFixtures {
+    /// Write the results recorded so far to the fixture file, instead of waiting for the [QueryFixtures::throttle_time].
+    ///
+    /// Does nothing when replaying.
+    pub fn flush(&self) {
+        self.state.save();
+    }
+}
+
+/// Record or replay the results of some query types, see [QueryFixtures].
+///
+/// Call it at the root of the app, before any of those queries is used.
+pub fn use_query_fixtures(init: impl FnOnce() -> QueryFixtures) -> UseQueryFixtures {
+    use_hook(|| UseQueryFixtures {
+        state: init().mount(),
+    })
+}
@@ -0,0 +1,55 @@
+use dioxus::prelude::*;
+use dioxus_core::{provide_root_context, spawn_forever};
+
+use crate::client::QueryClient;
+
+/// Tracks whether the app is focused, e.g its window or browser tab, so the queries that use
+/// [crate::query::Query::refetch_on_focus] are rerun when it regains focus.
+///
+/// The renderer integration is expected to report the focus changes through [FocusManager::set_focused],
+/// e.g from the window focus events on desktop or `visibilitychange` on web.
+/// Tests can also call it directly to simulate them.
+#[derive(Clone, Copy)]
+pub struct FocusManager {
+    focused: Signal<bool>,
+}
+
+impl FocusManager {
+    /// Get the [FocusManager] of this app.
+    pub fn current() -> Self {
+        match try_consume_context::<FocusManager>() {
+            Some(focus_manager) => focus_manager,
+            None => provide_root_context(FocusManager {
+                focused: Signal::new_in_scope(true, ScopeId::ROOT),
+            }),
+        }
+    }
+
+    /// Check if the app is focused. Defaults to `true`.
+    ///
+    /// This **will** automatically subscribe.
+    pub fn is_focused(&self) -> bool {
+        *self.focused.read()
+    }
+
+    /// Report whether the app is focused.
+    /// Regaining focus reruns the queries that use [crate::query::Query::refetch_on_focus].
+    pub fn set_focused(&self, focused: bool) {
+        let mut focused_signal = self.focused;
+        let was_focused = *focused_signal.peek();
+        if was_focused == focused {
+            return;
+        }
+        focused_signal.set(focused);
+
+        if focused {
+            let client = QueryClient::current();
+            spawn_forever(async move { client.refetch_on_focus().await });
+        }
+    }
+}
+
+/// Get the [FocusManager] to report whether the app is focused.
+pub fn use_focus_manager() -> FocusManager {
+    use_hook(FocusManager::current)
+}
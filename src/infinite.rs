@@ -0,0 +1,421 @@
+use core::fmt;
+use std::{future::Future, hash::Hash, rc::Rc};
+
+use dioxus::prelude::*;
+
+use crate::query::{
+    use_query, Query, QueryCapability, QueryContext, QueryError, QueryReader, QueryTrigger, Tag,
+    UseQuery,
+};
+
+/// Queries whose data is made of a list of pages, like feeds or paginated lists.
+///
+/// Use them with [use_infinite_query] by wrapping them in [Infinite].
+pub trait InfiniteQueryCapability
+where
+    Self: 'static + Clone + PartialEq + Hash + Eq,
+{
+    type Page;
+    type Err;
+    type Keys: Hash + PartialEq + Clone;
+    type PageParam: Clone;
+
+    /// Param of the first page to load.
+    fn initial_page_param(&self, keys: &Self::Keys) -> Self::PageParam;
+
+    /// Page logic.
+    fn run_page(
+        &self,
+        keys: &Self::Keys,
+        page_param: &Self::PageParam,
+    ) -> impl Future<Output = Result<Self::Page, Self::Err>>;
+
+    /// Param of the page that comes after the last loaded page, or [None] if there are no more pages.
+    fn next_page_param(
+        &self,
+        last_page: &Self::Page,
+        last_page_param: &Self::PageParam,
+    ) -> Option<Self::PageParam>;
+
+    /// Param of the page that comes before the first loaded page, or [None] if there are no more pages.
+    ///
+    /// Defaults to [None].
+    fn previous_page_param(
+        &self,
+        _first_page: &Self::Page,
+        _first_page_param: &Self::PageParam,
+    ) -> Option<Self::PageParam> {
+        None
+    }
+
+    /// Implement a custom logic to check if this query should be invalidated or not given a [InfiniteQueryCapability::Keys].
+    fn matches(&self, _keys: &Self::Keys) -> bool {
+        true
+    }
+
+    /// Tags of this query given its [InfiniteQueryCapability::Keys], see [QueryCapability::tags].
+    fn tags(&self, _keys: &Self::Keys) -> Vec<Tag> {
+        Vec::new()
+    }
+}
+
+/// The loaded pages of an [InfiniteQueryCapability], in order.
+pub struct InfiniteData<Q: InfiniteQueryCapability> {
+    pages: Vec<Rc<Q::Page>>,
+    page_params: Vec<Q::PageParam>,
+    page_error: Option<Rc<Q::Err>>,
+}
+
+impl<Q: InfiniteQueryCapability> Clone for InfiniteData<Q> {
+    fn clone(&self) -> Self {
+        Self {
+            pages: self.pages.clone(),
+            page_params: self.page_params.clone(),
+            page_error: self.page_error.clone(),
+        }
+    }
+}
+
+impl<Q> fmt::Debug for InfiniteData<Q>
+where
+    Q: InfiniteQueryCapability,
+    Q::Page: fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.pages()).finish()
+    }
+}
+
+impl<Q: InfiniteQueryCapability> InfiniteData<Q> {
+    /// Iterate over the loaded pages.
+    pub fn pages(&self) -> impl Iterator<Item = &Q::Page> {
+        self.pages.iter().map(|page| page.as_ref())
+    }
+
+    /// The params of the loaded pages.
+    pub fn page_params(&self) -> &[Q::PageParam] {
+        &self.page_params
+    }
+
+    /// How many pages are loaded.
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Check if there are no pages loaded.
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// The error of the last fetch of the next or previous page, if it failed.
+    ///
+    /// The loaded pages are kept meanwhile, so these errors are not retried like those of the first page or refetches.
+    /// It's cleared once the next or previous page is fetched again.
+    pub fn page_error(&self) -> Option<&Q::Err> {
+        self.page_error.as_deref()
+    }
+
+    fn next_page_param(&self, query: &Q) -> Option<Q::PageParam> {
+        query.next_page_param(self.pages.last()?, self.page_params.last()?)
+    }
+
+    fn previous_page_param(&self, query: &Q) -> Option<Q::PageParam> {
+        query.previous_page_param(self.pages.first()?, self.page_params.first()?)
+    }
+}
+
+/// Adapts an [InfiniteQueryCapability] into a [QueryCapability] so it can be cached in the [crate::query::QueriesStorage].
+///
+/// Refetching it, e.g when invalidated or in intervals, fetches all the loaded pages again in order.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Infinite<Q: InfiniteQueryCapability>(pub Q);
+
+impl<Q: InfiniteQueryCapability> Infinite<Q> {
+    /// Fetch all the pages starting from `page_param`, up to `count` pages.
+    async fn run_pages(
+        &self,
+        keys: &Q::Keys,
+        mut page_param: Q::PageParam,
+        count: usize,
+    ) -> Result<InfiniteData<Q>, Q::Err> {
+        let mut data = InfiniteData {
+            pages: Vec::with_capacity(count),
+            page_params: Vec::with_capacity(count),
+            page_error: None,
+        };
+        loop {
+            let page = self.0.run_page(keys, &page_param).await?;
+            data.pages.push(Rc::new(page));
+            data.page_params.push(page_param);
+
+            if data.len() >= count {
+                return Ok(data);
+            }
+            match data.next_page_param(&self.0) {
+                Some(next_page_param) => page_param = next_page_param,
+                None => return Ok(data),
+            }
+        }
+    }
+}
+
+impl<Q: InfiniteQueryCapability> QueryCapability for Infinite<Q> {
+    type Ok = InfiniteData<Q>;
+    type Err = Q::Err;
+    type Keys = Q::Keys;
+
+    async fn run(&self, keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        self.run_pages(keys, self.0.initial_page_param(keys), 1)
+            .await
+    }
+
+    async fn run_with_context(
+        &self,
+        keys: &Self::Keys,
+        ctx: &QueryContext<Self>,
+    ) -> Result<Self::Ok, Self::Err> {
+        let previous = ctx.with_previous(|previous| match previous {
+            Some(Ok(data)) if !data.is_empty() => Some(data.clone()),
+            _ => None,
+        });
+        let Some(previous) = previous else {
+            return self.run(keys).await;
+        };
+
+        match ctx.trigger() {
+            // Keep the loaded pages if the page fails, see [InfiniteData::page_error]
+            QueryTrigger::FetchNextPage => {
+                let mut data = previous;
+                data.page_error = None;
+                if let Some(page_param) = data.next_page_param(&self.0) {
+                    match self.0.run_page(keys, &page_param).await {
+                        Ok(page) => {
+                            data.pages.push(Rc::new(page));
+                            data.page_params.push(page_param);
+                        }
+                        Err(err) => data.page_error = Some(Rc::new(err)),
+                    }
+                }
+                Ok(data)
+            }
+            QueryTrigger::FetchPreviousPage => {
+                let mut data = previous;
+                data.page_error = None;
+                if let Some(page_param) = data.previous_page_param(&self.0) {
+                    match self.0.run_page(keys, &page_param).await {
+                        Ok(page) => {
+                            data.pages.insert(0, Rc::new(page));
+                            data.page_params.insert(0, page_param);
+                        }
+                        Err(err) => data.page_error = Some(Rc::new(err)),
+                    }
+                }
+                Ok(data)
+            }
+            _ => {
+                // Refetch all the loaded pages
+                let page_param = previous.page_params[0].clone();
+                self.run_pages(keys, page_param, previous.len()).await
+            }
+        }
+    }
+
+    fn matches(&self, keys: &Self::Keys) -> bool {
+        self.0.matches(keys)
+    }
+
+    fn tags(&self, keys: &Self::Keys) -> Vec<Tag> {
+        self.0.tags(keys)
+    }
+}
+
+pub struct UseInfiniteQuery<Q: InfiniteQueryCapability> {
+    query: UseQuery<Infinite<Q>>,
+    /// The pages being fetched through this hook, by [QueryTrigger::FetchNextPage] or [QueryTrigger::FetchPreviousPage].
+    fetching_pages: Signal<Vec<QueryTrigger>>,
+}
+
+impl<Q: InfiniteQueryCapability> Clone for UseInfiniteQuery<Q> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Q: InfiniteQueryCapability> Copy for UseInfiniteQuery<Q> {}
+
+impl<Q: InfiniteQueryCapability> UseInfiniteQuery<Q> {
+    /// The underlying [UseQuery], for e.g invalidating or cancelling it.
+    pub fn query(&self) -> UseQuery<Infinite<Q>> {
+        self.query
+    }
+
+    /// Read the [Query] state.
+    ///
+    /// This **will** automatically subscribe.
+    /// If you want a **non-subscribing** method have a look at [UseInfiniteQuery::peek].
+    pub fn read(&self) -> QueryReader<Infinite<Q>> {
+        self.query.read()
+    }
+
+    /// Read the [Query] state, see [UseInfiniteQuery::read].
+    pub fn try_read(&self) -> Result<QueryReader<Infinite<Q>>, QueryError> {
+        self.query.try_read()
+    }
+
+    /// Read the [Query] state.
+    ///
+    /// This **will not** automatically subscribe.
+    /// If you want a **subscribing** method have a look at [UseInfiniteQuery::read].
+    pub fn peek(&self) -> QueryReader<Infinite<Q>> {
+        self.query.peek()
+    }
+
+    /// Read the [Query] state without subscribing, see [UseInfiniteQuery::peek].
+    pub fn try_peek(&self) -> Result<QueryReader<Infinite<Q>>, QueryError> {
+        self.query.try_peek()
+    }
+
+    /// Check if there is a page after the last loaded one.
+    ///
+    /// This **will** automatically subscribe.
+    pub fn has_next_page(&self) -> bool {
+        self.try_has_next_page().unwrap()
+    }
+
+    /// Check if there is a page after the last loaded one, see [UseInfiniteQuery::has_next_page].
+    pub fn try_has_next_page(&self) -> Result<bool, QueryError> {
+        let reader = self.try_read()?;
+        let query = self.query.peek_capability();
+        let state = reader.state();
+        Ok(state
+            .ok()
+            .and_then(|data| data.next_page_param(&query.0))
+            .is_some())
+    }
+
+    /// Check if there is a page before the first loaded one.
+    ///
+    /// This **will** automatically subscribe.
+    pub fn has_previous_page(&self) -> bool {
+        self.try_has_previous_page().unwrap()
+    }
+
+    /// Check if there is a page before the first loaded one, see [UseInfiniteQuery::has_previous_page].
+    pub fn try_has_previous_page(&self) -> Result<bool, QueryError> {
+        let reader = self.try_read()?;
+        let query = self.query.peek_capability();
+        let state = reader.state();
+        Ok(state
+            .ok()
+            .and_then(|data| data.previous_page_param(&query.0))
+            .is_some())
+    }
+
+    /// Check if the next page is being fetched through this hook.
+    ///
+    /// This **will** automatically subscribe.
+    pub fn is_fetching_next_page(&self) -> bool {
+        self.fetching_pages
+            .read()
+            .contains(&QueryTrigger::FetchNextPage)
+    }
+
+    /// Check if the previous page is being fetched through this hook.
+    ///
+    /// This **will** automatically subscribe.
+    pub fn is_fetching_previous_page(&self) -> bool {
+        self.fetching_pages
+            .read()
+            .contains(&QueryTrigger::FetchPreviousPage)
+    }
+
+    /// Get a future that fetches a page for the given trigger, tracking it meanwhile.
+    fn try_fetch_page(
+        self,
+        trigger: QueryTrigger,
+    ) -> Result<impl Future<Output = ()> + 'static, QueryError> {
+        let run = self.query.try_run_for_trigger(trigger)?;
+        let mut fetching_pages = self.fetching_pages;
+
+        Ok(async move {
+            fetching_pages.write().push(trigger);
+
+            run.await;
+
+            // The hook might have been dropped meanwhile
+            if let Ok(mut fetching_pages) = fetching_pages.try_write() {
+                if let Some(i) = fetching_pages.iter().position(|page| *page == trigger) {
+                    fetching_pages.remove(i);
+                }
+            };
+        })
+    }
+
+    /// Fetch the page after the last loaded one and await its result.
+    ///
+    /// For a `sync` version use [UseInfiniteQuery::fetch_next_page].
+    pub async fn fetch_next_page_async(&self) -> QueryReader<Infinite<Q>> {
+        self.try_fetch_next_page_async().await.unwrap()
+    }
+
+    /// Fetch the page after the last loaded one and await its result, see [UseInfiniteQuery::fetch_next_page_async].
+    pub async fn try_fetch_next_page_async(&self) -> Result<QueryReader<Infinite<Q>>, QueryError> {
+        self.try_fetch_page(QueryTrigger::FetchNextPage)?.await;
+        self.try_peek()
+    }
+
+    /// Fetch the page after the last loaded one in the background.
+    ///
+    /// For an `async` version use [UseInfiniteQuery::fetch_next_page_async].
+    pub fn fetch_next_page(&self) {
+        self.try_fetch_next_page().unwrap()
+    }
+
+    /// Fetch the page after the last loaded one in the background, see [UseInfiniteQuery::fetch_next_page].
+    pub fn try_fetch_next_page(&self) -> Result<(), QueryError> {
+        spawn(self.try_fetch_page(QueryTrigger::FetchNextPage)?);
+        Ok(())
+    }
+
+    /// Fetch the page before the first loaded one and await its result.
+    ///
+    /// For a `sync` version use [UseInfiniteQuery::fetch_previous_page].
+    pub async fn fetch_previous_page_async(&self) -> QueryReader<Infinite<Q>> {
+        self.try_fetch_previous_page_async().await.unwrap()
+    }
+
+    /// Fetch the page before the first loaded one and await its result, see [UseInfiniteQuery::fetch_previous_page_async].
+    pub async fn try_fetch_previous_page_async(
+        &self,
+    ) -> Result<QueryReader<Infinite<Q>>, QueryError> {
+        self.try_fetch_page(QueryTrigger::FetchPreviousPage)?.await;
+        self.try_peek()
+    }
+
+    /// Fetch the page before the first loaded one in the background.
+    ///
+    /// For an `async` version use [UseInfiniteQuery::fetch_previous_page_async].
+    pub fn fetch_previous_page(&self) {
+        self.try_fetch_previous_page().unwrap()
+    }
+
+    /// Fetch the page before the first loaded one in the background, see [UseInfiniteQuery::fetch_previous_page].
+    pub fn try_fetch_previous_page(&self) -> Result<(), QueryError> {
+        spawn(self.try_fetch_page(QueryTrigger::FetchPreviousPage)?);
+        Ok(())
+    }
+}
+
+/// Infinite queries are [Query]s whose data is a list of pages that can be extended on demand,
+/// e.g when scrolling through a feed.
+///
+/// All the loaded pages are cached together in a single entry, which follows the same
+/// stale, clean and interval times as any other [Query]. When refetched, all the loaded pages are fetched again in order.
+pub fn use_infinite_query<Q: InfiniteQueryCapability>(
+    query: Query<Infinite<Q>>,
+) -> UseInfiniteQuery<Q> {
+    UseInfiniteQuery {
+        query: use_query(query),
+        fetching_pages: use_signal(Vec::new),
+    }
+}
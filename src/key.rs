@@ -0,0 +1,261 @@
+use core::fmt;
+use std::{any, hash::Hash, rc::Rc};
+
+use crate::query::QueryCapability;
+
+pub use dioxus_query_macros::QueryKey;
+
+trait DynSegment {
+    fn as_any(&self) -> &dyn any::Any;
+
+    fn dyn_eq(&self, other: &dyn DynSegment) -> bool;
+}
+
+impl<T: PartialEq + 'static> DynSegment for T {
+    fn as_any(&self) -> &dyn any::Any {
+        self
+    }
+
+    fn dyn_eq(&self, other: &dyn DynSegment) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+}
+
+/// A single part of a [QueryKey], either a value, a struct made of fields or the [Any] wildcard.
+///
+/// Values and structs keep their type, so segments of different types never match, e.g `UserId(4)` and `PostId(4)`.
+#[derive(Clone)]
+pub struct KeySegment(Segment);
+
+#[derive(Clone)]
+enum Segment {
+    Any,
+    Value {
+        type_name: &'static str,
+        value: Rc<dyn DynSegment>,
+    },
+    Fields {
+        type_id: any::TypeId,
+        type_name: &'static str,
+        fields: Rc<[KeySegment]>,
+    },
+}
+
+impl KeySegment {
+    /// A segment made of the given value.
+    pub fn value<T: PartialEq + 'static>(value: T) -> Self {
+        Self(Segment::Value {
+            type_name: any::type_name::<T>(),
+            value: Rc::new(value),
+        })
+    }
+
+    /// A segment made of the segments of the fields of `T`, one per field.
+    pub fn fields<T: 'static>(fields: Vec<KeySegment>) -> Self {
+        Self(Segment::Fields {
+            type_id: any::TypeId::of::<T>(),
+            type_name: any::type_name::<T>(),
+            fields: fields.into(),
+        })
+    }
+
+    /// A segment made of the given key, grouping its segments if it has more than one.
+    pub fn of<T: QueryKey + 'static>(key: &T) -> Self {
+        let mut segments = key.segments();
+        if segments.len() == 1 {
+            segments.remove(0)
+        } else {
+            Self::fields::<T>(segments)
+        }
+    }
+
+    /// A segment that matches any value.
+    pub fn any() -> Self {
+        Self(Segment::Any)
+    }
+
+    /// Check if this segment, used as pattern, matches the given one.
+    pub fn matches(&self, segment: &KeySegment) -> bool {
+        match (&self.0, &segment.0) {
+            (Segment::Any, _) => true,
+            (Segment::Value { value: pattern, .. }, Segment::Value { value, .. }) => {
+                pattern.dyn_eq(value.as_ref())
+            }
+            (
+                Segment::Fields {
+                    type_id: pattern_type_id,
+                    fields: pattern,
+                    ..
+                },
+                Segment::Fields {
+                    type_id, fields, ..
+                },
+            ) => {
+                pattern_type_id == type_id
+                    && pattern.len() == fields.len()
+                    && pattern
+                        .iter()
+                        .zip(fields.iter())
+                        .all(|(pattern, field)| pattern.matches(field))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Debug for KeySegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Segment::Any => f.write_str("Any"),
+            Segment::Value { type_name, .. } => f.write_str(type_name),
+            Segment::Fields {
+                type_name, fields, ..
+            } => {
+                let mut tuple = f.debug_tuple(type_name);
+                for field in fields.iter() {
+                    tuple.field(field);
+                }
+                tuple.finish()
+            }
+        }
+    }
+}
+
+/// Keys made of segments that go from the most general to the most specific, e.g `(UserId(4), PostId(2))`.
+///
+/// This allows matching keys by prefix and with wildcards, e.g `(UserId(4), Any)` or just `UserId(4)`
+/// match all the keys of the user `4`. See [KeyPattern].
+///
+/// It's implemented for primitives, strings and tuples of [QueryKey]s, and can be derived for structs and enums.
+/// Tuples are made of the segments of their elements, while structs and enums are a single segment of their own type.
+pub trait QueryKey {
+    /// The segments of this key, in order.
+    fn segments(&self) -> Vec<KeySegment>;
+
+    /// Check if this key matches the given pattern.
+    ///
+    /// It does when every segment of the pattern matches the segment of this key in the same position,
+    /// so shorter patterns match as prefixes.
+    fn matches_pattern(&self, pattern: &impl QueryKey) -> bool {
+        let segments = self.segments();
+        let pattern = pattern.segments();
+        pattern.len() <= segments.len()
+            && pattern
+                .iter()
+                .zip(segments.iter())
+                .all(|(pattern, segment)| pattern.matches(segment))
+    }
+}
+
+/// A [QueryKey] used as pattern to match the keys of the queries, see [crate::query::QueriesStorage::invalidate_matching].
+#[derive(Clone, Copy, Debug)]
+pub struct KeyPattern<P: QueryKey>(pub P);
+
+/// What [crate::query::QueriesStorage::invalidate_matching] and [crate::query::QueriesStorage::cancel_matching] accept
+/// to find the queries to act on, either their [QueryCapability::Keys] or a [KeyPattern].
+pub trait MatchKeys<K> {
+    /// Check if the given query with the given keys matches.
+    fn matches_query<Q: QueryCapability<Keys = K>>(&self, query: &Q, keys: &K) -> bool;
+}
+
+/// Keys are checked with [QueryCapability::matches].
+impl<K: Hash> MatchKeys<K> for K {
+    fn matches_query<Q: QueryCapability<Keys = K>>(&self, query: &Q, _keys: &K) -> bool {
+        query.matches(self)
+    }
+}
+
+/// Patterns are checked against the keys of every query, see [QueryKey::matches_pattern].
+impl<K: QueryKey, P: QueryKey> MatchKeys<K> for KeyPattern<P> {
+    fn matches_query<Q: QueryCapability<Keys = K>>(&self, _query: &Q, keys: &K) -> bool {
+        keys.matches_pattern(&self.0)
+    }
+}
+
+/// Wildcard [QueryKey] segment that matches any value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Any;
+
+impl QueryKey for Any {
+    fn segments(&self) -> Vec<KeySegment> {
+        vec![KeySegment::any()]
+    }
+}
+
+impl<T: QueryKey> QueryKey for &T {
+    fn segments(&self) -> Vec<KeySegment> {
+        (*self).segments()
+    }
+}
+
+macro_rules! impl_value_key {
+    ($($ty:ty),*) => {
+        $(
+            impl QueryKey for $ty {
+                fn segments(&self) -> Vec<KeySegment> {
+                    vec![KeySegment::value(self.clone())]
+                }
+            }
+        )*
+    };
+}
+
+impl_value_key!(
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    String,
+    &'static str
+);
+
+impl<T: PartialEq + Clone + 'static> QueryKey for Option<T> {
+    fn segments(&self) -> Vec<KeySegment> {
+        vec![KeySegment::value(self.clone())]
+    }
+}
+
+impl<T: PartialEq + Clone + 'static> QueryKey for Vec<T> {
+    fn segments(&self) -> Vec<KeySegment> {
+        vec![KeySegment::value(self.clone())]
+    }
+}
+
+macro_rules! impl_tuple_key {
+    ($($name:ident),*) => {
+        impl<$($name: QueryKey),*> QueryKey for ($($name,)*) {
+            #[allow(non_snake_case)]
+            fn segments(&self) -> Vec<KeySegment> {
+                let ($($name,)*) = self;
+                let mut segments = Vec::new();
+                $(segments.extend($name.segments());)*
+                segments
+            }
+        }
+    };
+}
+
+impl QueryKey for () {
+    fn segments(&self) -> Vec<KeySegment> {
+        Vec::new()
+    }
+}
+
+impl_tuple_key!(A);
+impl_tuple_key!(A, B);
+impl_tuple_key!(A, B, C);
+impl_tuple_key!(A, B, C, D);
+impl_tuple_key!(A, B, C, D, E);
+impl_tuple_key!(A, B, C, D, E, F);
+impl_tuple_key!(A, B, C, D, E, F, G);
+impl_tuple_key!(A, B, C, D, E, F, G, H);
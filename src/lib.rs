@@ -1,11 +1,65 @@
 #![doc = include_str!("../README.md")]
 
+/// Emit a `tracing` event when the `tracing` feature is enabled.
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::debug!(target: "dioxus_query", $($arg)*)
+    };
+}
+
+/// Instrument a future with a `tracing` span when the `tracing` feature is enabled.
+macro_rules! trace_span {
+    ($future:expr, $($arg:tt)*) => {{
+        #[cfg(feature = "tracing")]
+        let future = ::tracing::Instrument::instrument(
+            $future,
+            ::tracing::debug_span!(target: "dioxus_query", $($arg)*),
+        );
+        #[cfg(not(feature = "tracing"))]
+        let future = $future;
+        future
+    }};
+}
+
 pub mod captured;
+pub mod client;
+pub mod clock;
+#[cfg(feature = "serde")]
+pub mod dehydrate;
+#[cfg(feature = "devtools")]
+pub mod devtools;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
+pub mod focus;
+pub mod infinite;
+pub mod key;
 pub mod mutation;
+pub mod online;
+#[cfg(feature = "persist")]
+pub mod persist;
 pub mod query;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 pub mod prelude {
     pub use crate::captured::*;
+    pub use crate::client::*;
+    pub use crate::clock::*;
+    #[cfg(feature = "serde")]
+    pub use crate::dehydrate::*;
+    #[cfg(feature = "devtools")]
+    pub use crate::devtools::*;
+    #[cfg(feature = "fixtures")]
+    pub use crate::fixtures::*;
+    pub use crate::focus::*;
+    pub use crate::infinite::*;
+    pub use crate::key::*;
     pub use crate::mutation::*;
+    pub use crate::online::*;
+    #[cfg(feature = "persist")]
+    pub use crate::persist::*;
     pub use crate::query::*;
+    #[cfg(feature = "testing")]
+    pub use crate::testing::*;
 }
@@ -13,14 +13,12 @@ use std::{
     time::Duration,
 };
 #[cfg(not(target_family = "wasm"))]
-use tokio::time;
-#[cfg(not(target_family = "wasm"))]
 use tokio::time::Instant;
 #[cfg(target_family = "wasm")]
-use wasmtimer::tokio as time;
-#[cfg(target_family = "wasm")]
 use web_time::Instant;
 
+use crate::clock::QueryClock;
+
 pub trait MutationCapability
 where
     Self: 'static + Clone + PartialEq + Hash + Eq,
@@ -183,11 +181,17 @@ impl<Q: MutationCapability> MutationsStorage<Q> {
 
         // Spawn clean up task if there no more reactive contexts
         if mutation_data.reactive_contexts.lock().unwrap().is_empty() {
+            trace_event!(
+                mutation = std::any::type_name::<Q>(),
+                clean_time_ms = mutation.clean_time.as_millis() as u64,
+                "Clean task scheduled"
+            );
             *mutation_data.clean_task.borrow_mut() = Some(spawn_forever(async move {
                 // Wait as long as the stale time is configured
-                time::sleep(mutation.clean_time).await;
+                QueryClock::current().sleep(mutation.clean_time).await;
 
                 // Finally clear the mutation
+                trace_event!(mutation = std::any::type_name::<Q>(), "Mutation evicted");
                 let mut storage = storage_clone.write();
                 storage.remove(&mutation);
             }));
@@ -195,6 +199,22 @@ impl<Q: MutationCapability> MutationsStorage<Q> {
     }
 
     async fn run(mutation: &Mutation<Q>, data: &MutationData<Q>, keys: Q::Keys) {
+        let run = async move {
+            trace_event!("Mutation started");
+            #[cfg(feature = "tracing")]
+            let start = Instant::now();
+
+            Self::run_mutation(mutation, data, keys).await;
+
+            trace_event!(
+                duration_ms = start.elapsed().as_millis() as u64,
+                "Mutation finished"
+            );
+        };
+        trace_span!(run, "mutation_run", mutation = std::any::type_name::<Q>()).await
+    }
+
+    async fn run_mutation(mutation: &Mutation<Q>, data: &MutationData<Q>, keys: Q::Keys) {
         // Set to Loading
         let res =
             mem::replace(&mut *data.state.borrow_mut(), MutationStateData::Pending).into_loading();
@@ -203,14 +223,24 @@ impl<Q: MutationCapability> MutationsStorage<Q> {
             reactive_context.mark_dirty();
         }
 
-        // Run
+        // Run, unless a failure was injected by its [crate::testing::MutationProbe]
+        #[cfg(feature = "testing")]
+        let _running = crate::client::QueryClient::current().track_mutation();
+        #[cfg(feature = "testing")]
+        let res = match crate::testing::MutationProbe::<Q>::intercept(&keys) {
+            Some(err) => Err(err),
+            None => mutation.mutation.run(&keys).await,
+        };
+        #[cfg(not(feature = "testing"))]
         let res = mutation.mutation.run(&keys).await;
+        trace_event!(ok = res.is_ok(), "Mutation settled");
 
         // Set to Settled
         mutation.mutation.on_settled(&keys, &res).await;
+        trace_event!("Mutation on_settled finished");
         *data.state.borrow_mut() = MutationStateData::Settled {
             res,
-            settlement_instant: Instant::now(),
+            settlement_instant: QueryClock::current().now(),
         };
         for reactive_context in data.reactive_contexts.lock().unwrap().iter() {
             reactive_context.mark_dirty();
@@ -253,7 +283,7 @@ pub struct MutationReader<Q: MutationCapability> {
 }
 
 impl<Q: MutationCapability> MutationReader<Q> {
-    pub fn state(&self) -> Ref<MutationStateData<Q>> {
+    pub fn state(&self) -> Ref<'_, MutationStateData<Q>> {
         self.state.borrow()
     }
 }
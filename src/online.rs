@@ -0,0 +1,91 @@
+use std::{pin::pin, sync::Arc};
+
+use dioxus::prelude::*;
+use dioxus::signals::CopyValue;
+use dioxus_core::{provide_root_context, spawn_forever, Task};
+use futures_util::{Stream, StreamExt};
+use tokio::sync::Notify;
+
+use crate::client::QueryClient;
+
+/// Tracks whether the app is online, so queries are paused while offline and resumed once back online,
+/// see [crate::query::Query::network_mode] and [crate::query::Query::refetch_on_reconnect].
+///
+/// The connectivity is reported through [OnlineManager::set_online], either manually or by
+/// forwarding the platform connectivity events with [OnlineManager::watch].
+/// Tests can also call it directly to simulate them.
+#[derive(Clone, Copy)]
+pub struct OnlineManager {
+    online: Signal<bool>,
+    notifier: CopyValue<Arc<Notify>>,
+}
+
+impl OnlineManager {
+    /// Get the [OnlineManager] of this app.
+    pub fn current() -> Self {
+        match try_consume_context::<OnlineManager>() {
+            Some(online_manager) => online_manager,
+            None => provide_root_context(OnlineManager {
+                online: Signal::new_in_scope(true, ScopeId::ROOT),
+                notifier: CopyValue::new_in_scope(Arc::default(), ScopeId::ROOT),
+            }),
+        }
+    }
+
+    /// Check if the app is online. Defaults to `true`.
+    ///
+    /// This **will** automatically subscribe.
+    pub fn is_online(&self) -> bool {
+        *self.online.read()
+    }
+
+    pub(crate) fn peek_online(&self) -> bool {
+        *self.online.peek()
+    }
+
+    /// Report whether the app is online.
+    /// Going back online resumes the paused queries and reruns those that use [crate::query::Query::refetch_on_reconnect].
+    pub fn set_online(&self, online: bool) {
+        let mut online_signal = self.online;
+        if *online_signal.peek() == online {
+            return;
+        }
+        online_signal.set(online);
+
+        if online {
+            self.notifier.peek().notify_waiters();
+
+            let client = QueryClient::current();
+            spawn_forever(async move { client.refetch_on_reconnect().await });
+        }
+    }
+
+    /// Report the connectivity changes of the given stream, e.g from the platform network events.
+    ///
+    /// The returned [Task] can be cancelled to stop watching.
+    pub fn watch(&self, changes: impl Stream<Item = bool> + 'static) -> Task {
+        let online_manager = *self;
+        spawn_forever(async move {
+            let mut changes = pin!(changes);
+            while let Some(online) = changes.next().await {
+                online_manager.set_online(online);
+            }
+        })
+    }
+
+    /// Wait until the app is online.
+    pub(crate) async fn wait_online(&self) {
+        loop {
+            let notified = self.notifier.peek().clone().notified_owned();
+            if self.peek_online() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Get the [OnlineManager] to report whether the app is online.
+pub fn use_online_manager() -> OnlineManager {
+    use_hook(OnlineManager::current)
+}
@@ -0,0 +1,175 @@
+use std::{fs, path::PathBuf, rc::Rc, time::Duration};
+
+use dioxus::prelude::*;
+use dioxus_core::spawn_forever;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    client::QueryClient,
+    clock::QueryClock,
+    dehydrate::{now, DehydratedState, HydrateOptions, QueryHydration, WallClock},
+    query::QueryCapability,
+};
+
+/// A snapshot of the persisted query types.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct PersistedClient {
+    /// The [PersistQueryClient::buster] this snapshot was made with.
+    pub buster: String,
+    /// When was this snapshot made, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The persisted queries.
+    pub state: DehydratedState,
+}
+
+/// Where to save and restore the [PersistedClient] from, e.g a file or the local storage.
+pub trait Persister {
+    /// Load the last saved snapshot, if any.
+    fn restore(&self) -> Option<PersistedClient>;
+
+    /// Save the given snapshot, replacing the previous one.
+    fn persist(&self, client: &PersistedClient);
+
+    /// Remove the saved snapshot.
+    fn remove(&self);
+}
+
+/// [Persister] that saves the snapshot as a JSON file.
+///
+/// IO errors are ignored, a missing or invalid file is treated as an empty cache.
+pub struct JsonFilePersister {
+    path: PathBuf,
+}
+
+impl JsonFilePersister {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Persister for JsonFilePersister {
+    fn restore(&self) -> Option<PersistedClient> {
+        let content = fs::read(&self.path).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    fn persist(&self, client: &PersistedClient) {
+        if let Ok(content) = serde_json::to_vec(client) {
+            let _ = fs::write(&self.path, content);
+        }
+    }
+
+    fn remove(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Persist the cached queries of the selected types so they can be restored the next time the app starts.
+///
+/// Restored queries start as settled with their last known result, and are revalidated once stale as usual.
+/// Use it with [use_persist_query_client].
+pub struct PersistQueryClient {
+    persister: Rc<dyn Persister>,
+    buster: String,
+    max_age: Duration,
+    throttle_time: Duration,
+    hydration: QueryHydration,
+}
+
+impl PersistQueryClient {
+    pub fn new(persister: impl Persister + 'static) -> Self {
+        Self {
+            persister: Rc::new(persister),
+            buster: String::new(),
+            max_age: Duration::from_secs(24 * 60 * 60),
+            throttle_time: Duration::from_secs(1),
+            hydration: QueryHydration::new(),
+        }
+    }
+
+    /// Version of the persisted cache, a snapshot made with a different buster is discarded.
+    /// Change it whenever the shape of the persisted data changes.
+    pub fn buster(self, buster: impl Into<String>) -> Self {
+        Self {
+            buster: buster.into(),
+            ..self
+        }
+    }
+
+    /// Discard snapshots and queries older than this. Defaults to `1 day`.
+    pub fn max_age(self, max_age: Duration) -> Self {
+        Self { max_age, ..self }
+    }
+
+    /// How long to wait after the cache changes before saving it, so the changes made meanwhile are saved at once.
+    /// Defaults to `1s`.
+    pub fn throttle_time(self, throttle_time: Duration) -> Self {
+        Self {
+            throttle_time,
+            ..self
+        }
+    }
+
+    /// Persist the queries of the given type.
+    ///
+    /// They are identified by their type name, so renaming the type discards its persisted queries.
+    pub fn persist<Q: QueryCapability>(self) -> Self
+    where
+        Q::Keys: Serialize + DeserializeOwned,
+        Q::Ok: Serialize + DeserializeOwned,
+        Q::Err: Serialize + DeserializeOwned,
+    {
+        Self {
+            hydration: self.hydration.query::<Q>(),
+            ..self
+        }
+    }
+
+    /// Restore the persisted queries and start saving them whenever they change.
+    fn mount(self) {
+        // Restore the last snapshot, unless it's outdated
+        if let Some(client) = self.persister.restore() {
+            let age = Duration::from_millis(now().saturating_sub(client.timestamp));
+            if client.buster != self.buster || age > self.max_age {
+                self.persister.remove();
+            } else {
+                self.hydration.hydrate_with(
+                    &client.state,
+                    &HydrateOptions {
+                        max_age: Some(self.max_age),
+                        skip_mount: false,
+                    },
+                );
+            }
+        }
+
+        // Save the snapshot whenever it changes
+        spawn_forever(async move {
+            let client = QueryClient::current();
+            let clock = QueryClock::current();
+            let wall_clock = WallClock::new();
+            let mut version = client.version();
+            loop {
+                client.changed(version).await;
+
+                // Save the changes made meanwhile at once
+                clock.sleep(self.throttle_time).await;
+                version = client.version();
+
+                let client = PersistedClient {
+                    buster: self.buster.clone(),
+                    timestamp: now(),
+                    state: self.hydration.dehydrate_with(&wall_clock),
+                };
+                self.persister.persist(&client);
+            }
+        });
+    }
+}
+
+/// Restore the persisted queries and keep saving them, see [PersistQueryClient].
+///
+/// Call it at the root of the app, before any of the persisted queries is used.
+pub fn use_persist_query_client(init: impl FnOnce() -> PersistQueryClient) {
+    use_hook(|| init().mount())
+}
@@ -1,30 +1,38 @@
 use core::fmt;
 use std::{
-    cell::{Ref, RefCell},
-    collections::{HashMap, HashSet},
+    borrow::Cow,
+    cell::{Cell, Ref, RefCell},
+    collections::{hash_map::RandomState, HashMap, HashSet},
     future::Future,
-    hash::Hash,
+    hash::{BuildHasher, Hash},
     mem,
     rc::Rc,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
     time::Duration,
 };
 
 use dioxus::prelude::*;
 use dioxus::signals::CopyValue;
 use dioxus_core::{
-    provide_root_context, spawn_forever, use_drop, ReactiveContext, SuspendedFuture, Task,
+    provide_root_context, queue_effect, spawn_forever, use_drop, ReactiveContext, Runtime,
+    SuspendedFuture, Task,
 };
 use futures_util::stream::{FuturesUnordered, StreamExt};
 use tokio::sync::Notify;
+
+use crate::{
+    captured::Captured, client::QueryClient, clock::QueryClock, key::MatchKeys,
+    online::OnlineManager,
+};
+/// The instants used by the queries, e.g for [Query::initial_data] or [crate::clock::Clock::now].
 #[cfg(not(target_family = "wasm"))]
-use tokio::time;
-#[cfg(not(target_family = "wasm"))]
-use tokio::time::Instant;
+pub use tokio::time::Instant;
+/// The instants used by the queries, e.g for [Query::initial_data] or [crate::clock::Clock::now].
 #[cfg(target_family = "wasm")]
-use wasmtimer::tokio as time;
-#[cfg(target_family = "wasm")]
-use web_time::Instant;
+pub use web_time::Instant;
 
 pub trait QueryCapability
 where
@@ -37,12 +45,199 @@ where
     /// Query logic.
     fn run(&self, keys: &Self::Keys) -> impl Future<Output = Result<Self::Ok, Self::Err>>;
 
+    /// Query logic with access to the [QueryContext] of the execution,
+    /// such as the retry attempt, the previous value or its cancellation signal.
+    ///
+    /// Defaults to calling [QueryCapability::run].
+    fn run_with_context(
+        &self,
+        keys: &Self::Keys,
+        _ctx: &QueryContext<Self>,
+    ) -> impl Future<Output = Result<Self::Ok, Self::Err>> {
+        self.run(keys)
+    }
+
     /// Implement a custom logic to check if this query should be invalidated or not given a [QueryCapability::Keys].
     fn matches(&self, _keys: &Self::Keys) -> bool {
         true
     }
+
+    /// Tags of this query given its [QueryCapability::Keys], e.g `"user:42"`.
+    /// They allow invalidating queries of different types at once with [crate::client::QueryClient::invalidate_tags].
+    ///
+    /// Defaults to no tags.
+    fn tags(&self, _keys: &Self::Keys) -> Vec<Tag> {
+        Vec::new()
+    }
+
+    /// Runs when the result of an execution is discarded, because the data of the query was written
+    /// while it was running, e.g with [QueriesStorage::set_data] or [QueriesStorage::update_data].
+    /// The written value is kept, and those waiting for the execution are resolved all the same.
+    ///
+    /// Cancelled executions never finish, so they are not discarded, see [RefetchMode::Cancel].
+    fn on_discarded(&self, _keys: &Self::Keys, _result: &Result<Self::Ok, Self::Err>) {}
+}
+
+/// Label of a query, see [QueryCapability::tags].
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Tag(Cow<'static, str>);
+
+impl Tag {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Tag {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&'static str> for Tag {
+    fn from(tag: &'static str) -> Self {
+        Self(Cow::Borrowed(tag))
+    }
+}
+
+impl From<String> for Tag {
+    fn from(tag: String) -> Self {
+        Self(Cow::Owned(tag))
+    }
+}
+
+/// Why was a query executed.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum QueryTrigger {
+    /// A subscriber was mounted and the cached value was stale.
+    Mount,
+    /// The [Query::interval_time] elapsed.
+    Interval,
+    /// The query was invalidated.
+    Invalidate,
+    /// The query was requested through [QueriesStorage::get].
+    Get,
+    /// The next page was requested, see [crate::infinite::UseInfiniteQuery::fetch_next_page].
+    FetchNextPage,
+    /// The previous page was requested, see [crate::infinite::UseInfiniteQuery::fetch_previous_page].
+    FetchPreviousPage,
+    /// The app regained focus, see [Query::refetch_on_focus].
+    Focus,
+    /// The app went back online, see [Query::refetch_on_reconnect].
+    Reconnect,
+}
+
+/// Signal to know whether a query execution has been cancelled.
+///
+/// It can be cloned and moved to other threads or tasks that work on behalf of the query.
+#[derive(Clone, Default)]
+pub struct CancelSignal {
+    cancelled: Arc<AtomicBool>,
+    notifier: Arc<Notify>,
+}
+
+impl CancelSignal {
+    /// Check if the execution has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Wait until the execution is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.notifier.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+        self.notifier.notify_waiters();
+    }
+}
+
+/// Information about an execution of a query, see [QueryCapability::run_with_context].
+pub struct QueryContext<Q: QueryCapability> {
+    attempt: u32,
+    trigger: QueryTrigger,
+    cancel_signal: CancelSignal,
+    state: Rc<RefCell<QueryStateData<Q>>>,
+}
+
+impl<Q: QueryCapability> QueryContext<Q> {
+    /// Retry attempt of this execution, starting at `0`.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// What caused this execution.
+    pub fn trigger(&self) -> QueryTrigger {
+        self.trigger
+    }
+
+    /// Signal that tells when this execution has been cancelled.
+    pub fn cancel_signal(&self) -> &CancelSignal {
+        &self.cancel_signal
+    }
+
+    /// Check if this execution has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_signal.is_cancelled()
+    }
+
+    /// Wait until this execution is cancelled.
+    pub async fn cancelled(&self) {
+        self.cancel_signal.cancelled().await
+    }
+
+    /// Visit the previous settled value of the query, if any.
+    pub fn with_previous<T>(&self, f: impl FnOnce(Option<&Result<Q::Ok, Q::Err>>) -> T) -> T {
+        f(self.state.borrow().value())
+    }
+
+    /// A copy of the previous settled value of the query, if any, see [QueryContext::with_previous].
+    pub fn previous(&self) -> Option<Result<Q::Ok, Q::Err>>
+    where
+        Q::Ok: Clone,
+        Q::Err: Clone,
+    {
+        self.with_previous(|previous| previous.cloned())
+    }
+}
+
+type QueryResult<Q> = Result<<Q as QueryCapability>::Ok, <Q as QueryCapability>::Err>;
+
+/// Error of the fallible query accessors, e.g [UseQuery::try_read].
+///
+/// The [UseQuery] accessors never fail because of an evicted entry, they create it again instead.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum QueryError {
+    /// The [QueriesStorage] of the query type was not found, e.g because it's used outside of the app.
+    MissingContext,
+    /// The query has no result yet.
+    NotSettled,
 }
 
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingContext => f.write_str("Queries storage not found."),
+            Self::NotSettled => f.write_str("Query is not settled."),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
 pub enum QueryStateData<Q: QueryCapability> {
     /// Has not loaded yet.
     Pending,
@@ -53,6 +248,9 @@ pub enum QueryStateData<Q: QueryCapability> {
         res: Result<Q::Ok, Q::Err>,
         settlement_instant: Instant,
     },
+    /// Is waiting for the app to be online to load and may not have a previous settled value,
+    /// see [Query::network_mode].
+    Paused { res: Option<Result<Q::Ok, Q::Err>> },
 }
 
 impl<Q: QueryCapability> TryFrom<QueryStateData<Q>> for Result<Q::Ok, Q::Err> {
@@ -62,6 +260,7 @@ impl<Q: QueryCapability> TryFrom<QueryStateData<Q>> for Result<Q::Ok, Q::Err> {
         match value {
             QueryStateData::Loading { res: Some(res) } => Ok(res),
             QueryStateData::Settled { res, .. } => Ok(res),
+            QueryStateData::Paused { res: Some(res) } => Ok(res),
             _ => Err(()),
         }
     }
@@ -78,6 +277,7 @@ where
             Self::Pending => f.write_str("Pending"),
             Self::Loading { res } => write!(f, "Loading {{ {res:?} }}"),
             Self::Settled { res, .. } => write!(f, "Settled {{ {res:?} }}"),
+            Self::Paused { res } => write!(f, "Paused {{ {res:?} }}"),
         }
     }
 }
@@ -98,19 +298,32 @@ impl<Q: QueryCapability> QueryStateData<Q> {
         matches!(self, QueryStateData::Loading { .. })
     }
 
+    /// Check if the state is [QueryStateData::Paused].
+    pub fn is_paused(&self) -> bool {
+        matches!(self, QueryStateData::Paused { .. })
+    }
+
     /// Check if the state is [QueryStateData::Pending].
     pub fn is_pending(&self) -> bool {
         matches!(self, QueryStateData::Pending)
     }
 
     /// Check if the state is stale or not, where stale means outdated.
+    ///
+    /// Uses the current instant of the [QueryClock], see [QueryStateData::is_stale_at].
     pub fn is_stale(&self, query: &Query<Q>) -> bool {
+        self.is_stale_at(query, QueryClock::current().now())
+    }
+
+    /// Check if the state is stale or not at the given instant, see [QueryStateData::is_stale].
+    pub fn is_stale_at(&self, query: &Query<Q>, now: Instant) -> bool {
         match self {
             QueryStateData::Pending => true,
             QueryStateData::Loading { .. } => true,
+            QueryStateData::Paused { .. } => true,
             QueryStateData::Settled {
                 settlement_instant, ..
-            } => Instant::now().duration_since(*settlement_instant) >= query.stale_time,
+            } => now.duration_since(*settlement_instant) >= query.stale_time,
         }
     }
 
@@ -119,16 +332,45 @@ impl<Q: QueryCapability> QueryStateData<Q> {
         match self {
             Self::Settled { res: Ok(res), .. } => Some(res),
             Self::Loading { res: Some(Ok(res)) } => Some(res),
+            Self::Paused { res: Some(Ok(res)) } => Some(res),
             _ => None,
         }
     }
 
     /// Get the value as an [Result] if possible, otherwise it will panic.
     pub fn unwrap(&self) -> &Result<Q::Ok, Q::Err> {
+        self.try_unwrap().unwrap()
+    }
+
+    /// Get the value as an [Result] if possible, otherwise [QueryError::NotSettled].
+    pub fn try_unwrap(&self) -> Result<&Result<Q::Ok, Q::Err>, QueryError> {
+        match self {
+            Self::Loading { res: Some(v) } => Ok(v),
+            Self::Settled { res, .. } => Ok(res),
+            Self::Paused { res: Some(v) } => Ok(v),
+            _ => Err(QueryError::NotSettled),
+        }
+    }
+
+    /// The name of this state, e.g `"Loading"`.
+    #[cfg(feature = "devtools")]
+    pub(crate) fn variant_name(&self) -> &'static str {
+        match self {
+            QueryStateData::Pending => "Pending",
+            QueryStateData::Loading { .. } => "Loading",
+            QueryStateData::Settled { res: Ok(_), .. } => "Settled (Ok)",
+            QueryStateData::Settled { res: Err(_), .. } => "Settled (Err)",
+            QueryStateData::Paused { .. } => "Paused",
+        }
+    }
+
+    /// Get the cached value if any.
+    fn value(&self) -> Option<&Result<Q::Ok, Q::Err>> {
         match self {
-            Self::Loading { res: Some(v) } => v,
-            Self::Settled { res, .. } => res,
-            _ => unreachable!(),
+            QueryStateData::Settled { res, .. }
+            | QueryStateData::Loading { res: Some(res) }
+            | QueryStateData::Paused { res: Some(res) } => Some(res),
+            _ => None,
         }
     }
 
@@ -137,11 +379,86 @@ impl<Q: QueryCapability> QueryStateData<Q> {
             QueryStateData::Pending => QueryStateData::Loading { res: None },
             QueryStateData::Loading { res } => QueryStateData::Loading { res },
             QueryStateData::Settled { res, .. } => QueryStateData::Loading { res: Some(res) },
+            QueryStateData::Paused { res } => QueryStateData::Loading { res },
+        }
+    }
+
+    fn into_paused(self) -> QueryStateData<Q> {
+        match self.into_loading() {
+            QueryStateData::Loading { res } => QueryStateData::Paused { res },
+            state => state,
+        }
+    }
+
+    /// Go back to the state previous to [QueryStateData::into_loading].
+    fn into_previous(self, settlement_instant: Option<Instant>) -> QueryStateData<Q> {
+        match (self, settlement_instant) {
+            (
+                QueryStateData::Loading { res: Some(res) }
+                | QueryStateData::Paused { res: Some(res) },
+                Some(settlement_instant),
+            ) => QueryStateData::Settled {
+                res,
+                settlement_instant,
+            },
+            (QueryStateData::Loading { .. } | QueryStateData::Paused { .. }, _) => {
+                QueryStateData::Pending
+            }
+            (state, _) => state,
+        }
+    }
+
+    fn settlement_instant(&self) -> Option<Instant> {
+        match self {
+            QueryStateData::Settled {
+                settlement_instant, ..
+            } => Some(*settlement_instant),
+            _ => None,
         }
     }
 }
+
+/// Hook into the executions of a query type, e.g to count or replace them in tests.
+pub(crate) trait QueryInterceptor<Q: QueryCapability> {
+    /// Called before every execution, returning a result replaces it.
+    fn intercept(&self, _keys: &Q::Keys) -> Option<Result<Q::Ok, Q::Err>> {
+        None
+    }
+
+    /// Called with the result of every attempt, including the retried ones.
+    fn ran(&self, _keys: &Q::Keys, _res: &Result<Q::Ok, Q::Err>) {}
+
+    /// Called with the final result of every execution, once it's not retried anymore.
+    fn settled(&self, _keys: &Q::Keys, _res: &Result<Q::Ok, Q::Err>) {}
+}
+
+type QueryInterceptors<Q> = Vec<Rc<dyn QueryInterceptor<Q>>>;
+
+#[cfg(feature = "testing")]
+type QueryMock<Q> = Rc<
+    dyn Fn(
+        &<Q as QueryCapability>::Keys,
+    ) -> Result<<Q as QueryCapability>::Ok, <Q as QueryCapability>::Err>,
+>;
+
 pub struct QueriesStorage<Q: QueryCapability> {
     storage: CopyValue<HashMap<Query<Q>, QueryData<Q>>>,
+    seeds: CopyValue<Vec<QuerySeed<Q>>>,
+    interceptors: CopyValue<QueryInterceptors<Q>>,
+    #[cfg(feature = "testing")]
+    mock: CopyValue<Option<QueryMock<Q>>>,
+    #[cfg(feature = "devtools")]
+    debug: CopyValue<Option<crate::devtools::DebugQuery<Q>>>,
+}
+
+/// Settled result to start a query with once its entry is created, e.g restored from disk.
+struct QuerySeed<Q: QueryCapability> {
+    keys: Q::Keys,
+    res: Result<Q::Ok, Q::Err>,
+    settlement_instant: Instant,
+    /// Skip the first fetch on mount, e.g because it was just fetched during server-side rendering.
+    /// Only until the render that hydrated it is done, see [QueriesStorage::end_hydration].
+    hydrated: bool,
 }
 
 impl<Q: QueryCapability> Copy for QueriesStorage<Q> {}
@@ -157,6 +474,16 @@ struct QuerySuspenseData {
     task: Task,
 }
 
+/// A running execution of a query, shared by everyone that requested it.
+struct QueryFetch {
+    task: Task,
+    notifier: Arc<Notify>,
+    cancel_signal: CancelSignal,
+    trigger: QueryTrigger,
+    /// When was the query settled before this execution, used to restore it if cancelled.
+    settlement_instant: Option<Instant>,
+}
+
 pub struct QueryData<Q: QueryCapability> {
     state: Rc<RefCell<QueryStateData<Q>>>,
     reactive_contexts: Arc<Mutex<HashSet<ReactiveContext>>>,
@@ -164,6 +491,14 @@ pub struct QueryData<Q: QueryCapability> {
     suspense_task: Rc<RefCell<Option<QuerySuspenseData>>>,
     interval_task: Rc<RefCell<Option<(Duration, Task)>>>,
     clean_task: Rc<RefCell<Option<Task>>>,
+    fetch: Rc<RefCell<Option<QueryFetch>>>,
+    /// Incremented on every execution and cache write, an execution only settles the query if it's still the latest when it finishes.
+    generation: Rc<Cell<u64>>,
+    /// Whether the query was hydrated and not mounted yet, see [QuerySeed::hydrated].
+    hydrated: Rc<Cell<bool>>,
+    /// Whether the running execution is waiting for the clock or to be online, which tests control.
+    #[cfg(feature = "testing")]
+    waiting: Rc<Cell<bool>>,
 }
 
 impl<Q: QueryCapability> Clone for QueryData<Q> {
@@ -175,28 +510,282 @@ impl<Q: QueryCapability> Clone for QueryData<Q> {
             suspense_task: self.suspense_task.clone(),
             interval_task: self.interval_task.clone(),
             clean_task: self.clean_task.clone(),
+            fetch: self.fetch.clone(),
+            generation: self.generation.clone(),
+            hydrated: self.hydrated.clone(),
+            #[cfg(feature = "testing")]
+            waiting: self.waiting.clone(),
+        }
+    }
+}
+
+impl<Q: QueryCapability> QueryData<Q> {
+    fn new(query: &Query<Q>) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(Self::initial_state(query))),
+            reactive_contexts: Arc::default(),
+            suspense_task: Rc::default(),
+            interval_task: Rc::default(),
+            clean_task: Rc::default(),
+            fetch: Rc::default(),
+            generation: Rc::default(),
+            hydrated: Rc::default(),
+            #[cfg(feature = "testing")]
+            waiting: Rc::default(),
+        }
+    }
+
+    fn initial_state(query: &Query<Q>) -> QueryStateData<Q> {
+        // Seed the initial data if any
+        match &query.initial_data {
+            Some((initial_data, updated_at)) => QueryStateData::Settled {
+                res: Ok(initial_data()),
+                settlement_instant: *updated_at,
+            },
+            None => QueryStateData::Pending,
+        }
+    }
+
+    fn has_subscribers(&self) -> bool {
+        !self.reactive_contexts.lock().unwrap().is_empty()
+    }
+
+    /// Unique identifier of this entry while it's alive.
+    #[cfg(feature = "devtools")]
+    fn id(&self) -> usize {
+        Rc::as_ptr(&self.state) as *const () as usize
+    }
+
+    /// Stop any running execution and go back to the initial state.
+    fn reset(&self, query: &Query<Q>) {
+        if let Some(fetch) = self.fetch.take() {
+            fetch.task.cancel();
+            fetch.cancel_signal.cancel();
+            fetch.notifier.notify_waiters();
+        }
+        self.generation.set(self.generation.get() + 1);
+
+        *self.state.borrow_mut() = Self::initial_state(query);
+        for reactive_context in self.reactive_contexts.lock().unwrap().iter() {
+            reactive_context.mark_dirty();
+        }
+        QueryClient::current().mark_changed();
+    }
+
+    /// Wait for the given future, which only the clock or the connectivity can resolve.
+    async fn wait(&self, future: impl Future<Output = ()>) {
+        #[cfg(feature = "testing")]
+        {
+            self.waiting.set(true);
+            QueryClient::current().notify_activity();
+        }
+        future.await;
+        #[cfg(feature = "testing")]
+        self.waiting.set(false);
+    }
+
+    /// Cancel the interval and clean tasks, for when the query is removed.
+    fn cancel_tasks(&self) {
+        if let Some((_, interval_task)) = self.interval_task.take() {
+            interval_task.cancel();
+        }
+        if let Some(clean_task) = self.clean_task.take() {
+            clean_task.cancel();
         }
     }
 }
 
 impl<Q: QueryCapability> QueriesStorage<Q> {
     fn new_in_root() -> Self {
-        Self {
+        let storage = Self {
             storage: CopyValue::new_in_scope(HashMap::default(), ScopeId::ROOT),
+            seeds: CopyValue::new_in_scope(Vec::new(), ScopeId::ROOT),
+            interceptors: CopyValue::new_in_scope(Vec::new(), ScopeId::ROOT),
+            #[cfg(feature = "testing")]
+            mock: CopyValue::new_in_scope(None, ScopeId::ROOT),
+            #[cfg(feature = "devtools")]
+            debug: CopyValue::new_in_scope(None, ScopeId::ROOT),
+        };
+
+        // Let the client manage this storage along with those of other query types
+        QueryClient::current().register(storage);
+
+        storage
+    }
+
+    /// Get the storage of this query type, creating it if necessary.
+    pub(crate) fn current() -> Self {
+        match try_consume_context::<QueriesStorage<Q>>() {
+            Some(storage) => storage,
+            None => provide_root_context(QueriesStorage::<Q>::new_in_root()),
+        }
+    }
+
+    /// Remove all the cached queries of this type that have no subscribers.
+    /// Those still in use are kept as they are, use [QueriesStorage::reset] to run them again from scratch.
+    pub fn clear(&self) {
+        let mut storage = self.storage;
+        storage.write().retain(|query, query_data| {
+            if query_data.has_subscribers() {
+                return true;
+            }
+
+            // Stop the running execution and the tasks of the removed queries
+            query_data.reset(query);
+            query_data.cancel_tasks();
+            false
+        });
+    }
+
+    /// Reset all the cached queries of this type to their initial state,
+    /// and await the re-execution of those that have subscribers.
+    pub async fn reset(&self) {
+        let queries = self
+            .storage
+            .peek()
+            .iter()
+            .map(|(query, query_data)| (query.clone(), query_data.clone()))
+            .collect::<Vec<_>>();
+
+        for (query, query_data) in &queries {
+            query_data.reset(query);
+        }
+
+        // Run again those queries that are in use
+        let active_queries = queries
+            .iter()
+            .filter(|(query, query_data)| query.enabled && query_data.has_subscribers())
+            .map(|(q, d)| (q, d))
+            .collect::<Vec<_>>();
+        Self::run_queries(&active_queries, QueryTrigger::Invalidate).await
+    }
+
+    /// Rerun those queries that are stale, have subscribers and use [Query::refetch_on_focus].
+    pub(crate) async fn refetch_on_focus(&self) {
+        self.refetch_stale(QueryTrigger::Focus, |query| query.refetch_on_focus)
+            .await
+    }
+
+    /// Rerun those queries that are stale, have subscribers and use [Query::refetch_on_reconnect].
+    pub(crate) async fn refetch_on_reconnect(&self) {
+        self.refetch_stale(QueryTrigger::Reconnect, |query| query.refetch_on_reconnect)
+            .await
+    }
+
+    /// Rerun those queries that are stale, have subscribers and pass the given filter.
+    async fn refetch_stale(&self, trigger: QueryTrigger, filter: impl Fn(&Query<Q>) -> bool) {
+        let now = QueryClock::current().now();
+        let queries = self
+            .storage
+            .peek()
+            .iter()
+            .filter(|(query, query_data)| {
+                filter(query)
+                    && query.enabled
+                    && query_data.has_subscribers()
+                    && query_data.state.borrow().is_stale_at(query, now)
+            })
+            .map(|(query, query_data)| (query.clone(), query_data.clone()))
+            .collect::<Vec<_>>();
+        let queries = queries.iter().map(|(q, d)| (q, d)).collect::<Vec<_>>();
+
+        Self::run_queries(&queries, trigger).await
+    }
+
+    /// Hook into every execution of the queries of this type.
+    #[cfg(any(feature = "testing", feature = "fixtures"))]
+    pub(crate) fn intercept(&self, interceptor: impl QueryInterceptor<Q> + 'static) {
+        let mut interceptors = self.interceptors;
+        interceptors.write().push(Rc::new(interceptor));
+    }
+
+    /// Check if any query of this type is running.
+    pub fn is_fetching(&self) -> bool {
+        self.storage
+            .peek()
+            .values()
+            .any(|query_data| query_data.fetch.borrow().is_some())
+    }
+
+    /// Create the data of a new entry, starting from its seed if any.
+    fn new_query_data(&self, query: &Query<Q>) -> QueryData<Q> {
+        let query_data = QueryData::new(query);
+        let mut seeds = self.seeds;
+        let mut seeds = seeds.write();
+        if let Some(i) = seeds.iter().position(|seed| seed.keys == query.keys) {
+            let seed = seeds.remove(i);
+            *query_data.state.borrow_mut() = QueryStateData::Settled {
+                res: seed.res,
+                settlement_instant: seed.settlement_instant,
+            };
+            query_data.hydrated.set(seed.hydrated);
+        }
+        query_data
+    }
+
+    /// Start the queries with the given keys that are created from now on as settled with the given result.
+    pub(crate) fn seed(
+        &self,
+        keys: Q::Keys,
+        res: Result<Q::Ok, Q::Err>,
+        settlement_instant: Instant,
+        hydrated: bool,
+    ) {
+        let mut seeds = self.seeds;
+        let mut seeds = seeds.write();
+        seeds.retain(|seed| seed.keys != keys);
+        seeds.push(QuerySeed {
+            keys,
+            res,
+            settlement_instant,
+            hydrated,
+        });
+    }
+
+    /// Once the current render is done, fetch the hydrated queries mounted from then on as usual if they are stale.
+    #[cfg(feature = "serde")]
+    pub(crate) fn end_hydration(&self) {
+        let storage = *self;
+        spawn_forever(async move {
+            let mut seeds = storage.seeds;
+            for seed in seeds.write().iter_mut() {
+                seed.hydrated = false;
+            }
+            for query_data in storage.storage.peek().values() {
+                query_data.hydrated.set(false);
+            }
+        });
+    }
+
+    /// Visit the last settled result of every query of this type, including the seeds not used yet.
+    #[cfg(feature = "serde")]
+    pub(crate) fn for_each_settled(
+        &self,
+        mut f: impl FnMut(&Q::Keys, &Result<Q::Ok, Q::Err>, Instant),
+    ) {
+        for seed in self.seeds.peek().iter() {
+            f(&seed.keys, &seed.res, seed.settlement_instant);
+        }
+        for (query, query_data) in self.storage.peek().iter() {
+            let settlement_instant = match &*query_data.fetch.borrow() {
+                Some(fetch) => fetch.settlement_instant,
+                None => query_data.state.borrow().settlement_instant(),
+            };
+            let state = query_data.state.borrow();
+            if let (Some(res), Some(settlement_instant)) = (state.value(), settlement_instant) {
+                f(&query.keys, res, settlement_instant);
+            }
         }
     }
 
     fn insert_or_get_query(&mut self, query: Query<Q>) -> QueryData<Q> {
         let query_clone = query.clone();
+        let storage_clone = *self;
         let mut storage = self.storage.write();
 
-        let query_data = storage.entry(query).or_insert_with(|| QueryData {
-            state: Rc::new(RefCell::new(QueryStateData::Pending)),
-            reactive_contexts: Arc::default(),
-            suspense_task: Rc::default(),
-            interval_task: Rc::default(),
-            clean_task: Rc::default(),
-        });
+        let query_data = storage
+            .entry(query)
+            .or_insert_with(|| storage_clone.new_query_data(&query_clone));
         let query_data_clone = query_data.clone();
 
         // Cancel clean task
@@ -227,10 +816,16 @@ impl<Q: QueryCapability> QueriesStorage<Q> {
             let task = spawn_forever(async move {
                 loop {
                     // Wait as long as the stale time is configured
-                    time::sleep(interval).await;
+                    QueryClock::current().sleep(interval).await;
+
+                    trace_event!(query = std::any::type_name::<Q>(), "Interval tick");
 
                     // Run the query
-                    QueriesStorage::<Q>::run_queries(&[(&query_clone, &query_data_clone)]).await;
+                    QueriesStorage::<Q>::run_queries(
+                        &[(&query_clone, &query_data_clone)],
+                        QueryTrigger::Interval,
+                    )
+                    .await;
                 }
             });
             *interval_task = Some((interval, task));
@@ -243,7 +838,10 @@ impl<Q: QueryCapability> QueriesStorage<Q> {
         let mut storage_clone = self.storage;
         let mut storage = self.storage.write();
 
-        let query_data = storage.get_mut(&query).unwrap();
+        // The entry might have been evicted already
+        let Some(query_data) = storage.get_mut(&query) else {
+            return;
+        };
 
         // Cancel interval task
         if let Some((_, interval_task)) = query_data.interval_task.take() {
@@ -252,13 +850,20 @@ impl<Q: QueryCapability> QueriesStorage<Q> {
 
         // Spawn clean up task if there no more reactive contexts
         if query_data.reactive_contexts.lock().unwrap().is_empty() {
+            trace_event!(
+                query = std::any::type_name::<Q>(),
+                clean_time_ms = query.clean_time.as_millis() as u64,
+                "Clean task scheduled"
+            );
             *query_data.clean_task.borrow_mut() = Some(spawn_forever(async move {
                 // Wait as long as the stale time is configured
-                time::sleep(query.clean_time).await;
+                QueryClock::current().sleep(query.clean_time).await;
 
                 // Finally clear the query
+                trace_event!(query = std::any::type_name::<Q>(), "Query evicted");
                 let mut storage = storage_clone.write();
                 storage.remove(&query);
+                QueryClient::current().mark_changed();
             }));
         }
     }
@@ -266,66 +871,49 @@ impl<Q: QueryCapability> QueriesStorage<Q> {
     pub async fn get(get_query: GetQuery<Q>) -> QueryReader<Q> {
         let query: Query<Q> = get_query.into();
 
-        let mut storage = match try_consume_context::<QueriesStorage<Q>>() {
-            Some(storage) => storage,
-            None => provide_root_context(QueriesStorage::<Q>::new_in_root()),
-        };
+        let mut storage = Self::current();
+        let storage_clone = storage;
 
         let query_data = storage
             .storage
             .write()
             .entry(query.clone())
-            .or_insert_with(|| QueryData {
-                state: Rc::new(RefCell::new(QueryStateData::Pending)),
-                reactive_contexts: Arc::default(),
-                suspense_task: Rc::default(),
-                interval_task: Rc::default(),
-                clean_task: Rc::default(),
-            })
+            .or_insert_with(|| storage_clone.new_query_data(&query))
             .clone();
 
         // Run the query if the value is stale
-        if query_data.state.borrow().is_stale(&query) {
-            // Set to Loading
-            let res = mem::replace(&mut *query_data.state.borrow_mut(), QueryStateData::Pending)
-                .into_loading();
-            *query_data.state.borrow_mut() = res;
-            for reactive_context in query_data.reactive_contexts.lock().unwrap().iter() {
-                reactive_context.mark_dirty();
-            }
-
-            // Run
-            let res = query.query.run(&query.keys).await;
-
-            // Set to Settled
-            *query_data.state.borrow_mut() = QueryStateData::Settled {
-                res,
-                settlement_instant: Instant::now(),
-            };
-            for reactive_context in query_data.reactive_contexts.lock().unwrap().iter() {
-                reactive_context.mark_dirty();
-            }
-
-            // Notify the suspense task if any
-            if let Some(suspense_task) = &*query_data.suspense_task.borrow() {
-                suspense_task.notifier.notify_waiters();
-            };
+        let is_stale = query_data.state.borrow().is_stale(&query);
+        trace_event!(
+            query = std::any::type_name::<Q>(),
+            is_stale,
+            "Query requested"
+        );
+        if is_stale {
+            Self::run_queries(&[(&query, &query_data)], QueryTrigger::Get).await;
         }
 
         // Spawn clean up task if there no more reactive contexts
         if query_data.reactive_contexts.lock().unwrap().is_empty() {
+            trace_event!(
+                query = std::any::type_name::<Q>(),
+                clean_time_ms = query.clean_time.as_millis() as u64,
+                "Clean task scheduled"
+            );
             *query_data.clean_task.borrow_mut() = Some(spawn_forever(async move {
                 // Wait as long as the stale time is configured
-                time::sleep(query.clean_time).await;
+                QueryClock::current().sleep(query.clean_time).await;
 
                 // Finally clear the query
+                trace_event!(query = std::any::type_name::<Q>(), "Query evicted");
                 let mut storage = storage.storage.write();
                 storage.remove(&query);
+                QueryClient::current().mark_changed();
             }));
         }
 
         QueryReader {
             state: query_data.state,
+            placeholder: None,
         }
     }
 
@@ -344,62 +932,736 @@ impl<Q: QueryCapability> QueriesStorage<Q> {
             .map(|(q, d)| (q, d))
             .collect::<Vec<_>>();
 
-        // Invalidate the queries
-        Self::run_queries(&matching_queries).await
+        // Invalidate the queries
+        Self::refetch_queries(&matching_queries).await
+    }
+
+    /// Invalidate those queries that match the given keys, see [QueryCapability::matches],
+    /// or whose keys match the given [crate::key::KeyPattern], e.g `KeyPattern((UserId(4), Any))`.
+    pub async fn invalidate_matching(matching_keys: impl MatchKeys<Q::Keys>) {
+        let storage = consume_context::<QueriesStorage<Q>>();
+
+        // Get those queries that match
+        let mut matching_queries = Vec::new();
+        for (query, data) in storage.storage.read().iter() {
+            if matching_keys.matches_query(&query.query, &query.keys) {
+                matching_queries.push((query.clone(), data.clone()));
+            }
+        }
+        let matching_queries = matching_queries
+            .iter()
+            .map(|(q, d)| (q, d))
+            .collect::<Vec<_>>();
+
+        // Invalidate the queries
+        Self::refetch_queries(&matching_queries).await
+    }
+
+    /// Invalidate those queries that have any of the given tags, see [QueryCapability::tags].
+    pub async fn invalidate_tags(tags: &[impl AsRef<str>]) {
+        let storage = consume_context::<QueriesStorage<Q>>();
+
+        // Get those queries that have any of the tags
+        let mut matching_queries = Vec::new();
+        for (query, data) in storage.storage.read().iter() {
+            let query_tags = query.query.tags(&query.keys);
+            let matches = query_tags
+                .iter()
+                .any(|query_tag| tags.iter().any(|tag| query_tag.as_str() == tag.as_ref()));
+            if matches {
+                matching_queries.push((query.clone(), data.clone()));
+            }
+        }
+        let matching_queries = matching_queries
+            .iter()
+            .map(|(q, d)| (q, d))
+            .collect::<Vec<_>>();
+
+        // Invalidate the queries
+        Self::refetch_queries(&matching_queries).await
+    }
+
+    /// Set the cached value of those queries with the given keys, without running them.
+    /// If none is cached yet, those created from now on start with this value, e.g to seed a detail query from a list.
+    ///
+    /// Any execution running meanwhile will have its result discarded.
+    pub fn set_data(keys: Q::Keys, value: Q::Ok)
+    where
+        Q::Ok: Clone,
+    {
+        Self::update_data(keys, |_| value.clone())
+    }
+
+    /// Update the cached value of those queries with the given keys, without running them.
+    /// `update` receives the current value, if any.
+    /// If none is cached yet, those created from now on start with the updated value, see [QueriesStorage::set_data].
+    ///
+    /// Any execution running meanwhile will have its result discarded.
+    pub fn update_data(keys: Q::Keys, mut update: impl FnMut(Option<&Q::Ok>) -> Q::Ok) {
+        let storage = Self::current();
+
+        // Get those queries with the same keys
+        let mut matching_queries = Vec::new();
+        for (query, data) in storage.storage.read().iter() {
+            if query.keys == keys {
+                matching_queries.push(data.clone());
+            }
+        }
+
+        // Not cached yet, so seed it instead
+        if matching_queries.is_empty() {
+            let mut seeds = storage.seeds;
+            let seed = {
+                let mut seeds = seeds.write();
+                seeds
+                    .iter()
+                    .position(|seed| seed.keys == keys)
+                    .map(|i| seeds.remove(i))
+            };
+            let value = update(seed.as_ref().and_then(|seed| seed.res.as_ref().ok()));
+            storage.seed(keys, Ok(value), QueryClock::current().now(), false);
+            QueryClient::current().mark_changed();
+            return;
+        }
+
+        for query_data in matching_queries {
+            // Discard the result of any running execution
+            query_data.generation.set(query_data.generation.get() + 1);
+
+            // Set to settled
+            let value = update(query_data.state.borrow().ok());
+            *query_data.state.borrow_mut() = QueryStateData::Settled {
+                res: Ok(value),
+                settlement_instant: QueryClock::current().now(),
+            };
+            for reactive_context in query_data.reactive_contexts.lock().unwrap().iter() {
+                reactive_context.mark_dirty();
+            }
+            QueryClient::current().mark_changed();
+
+            // Notify the suspense task if any
+            if let Some(suspense_task) = &*query_data.suspense_task.borrow() {
+                suspense_task.notifier.notify_waiters();
+            };
+        }
+    }
+
+    /// Cancel the running executions of those queries that match the given keys or [crate::key::KeyPattern],
+    /// setting them back to the state they had before starting.
+    pub fn cancel_matching(matching_keys: impl MatchKeys<Q::Keys>) {
+        let storage = consume_context::<QueriesStorage<Q>>();
+
+        // Get those queries that match
+        let mut matching_queries = Vec::new();
+        for (query, data) in storage.storage.read().iter() {
+            if matching_keys.matches_query(&query.query, &query.keys) {
+                matching_queries.push(data.clone());
+            }
+        }
+
+        // Cancel the queries
+        for query_data in matching_queries {
+            Self::cancel_fetch(&query_data);
+        }
+    }
+
+    /// Run the given queries, joining those that are already running.
+    async fn run_queries(queries: &[(&Query<Q>, &QueryData<Q>)], trigger: QueryTrigger) {
+        let tasks = FuturesUnordered::new();
+
+        for (query, query_data) in queries {
+            tasks.push(Self::fetch(query, query_data, RefetchMode::Join, trigger));
+        }
+
+        tasks.count().await;
+    }
+
+    /// Refetch the given queries, following their [RefetchMode] if they are already running.
+    async fn refetch_queries(queries: &[(&Query<Q>, &QueryData<Q>)]) {
+        let tasks = FuturesUnordered::new();
+
+        for (query, query_data) in queries {
+            tasks.push(Self::fetch(
+                query,
+                query_data,
+                query.refetch_mode,
+                QueryTrigger::Invalidate,
+            ));
+        }
+
+        tasks.count().await;
+    }
+
+    /// Run the query for the given trigger, only joining the running execution if it was started by the same trigger.
+    /// Otherwise it waits for it to finish and runs the query again.
+    pub(crate) async fn run_for_trigger(
+        query: &Query<Q>,
+        query_data: &QueryData<Q>,
+        trigger: QueryTrigger,
+    ) {
+        loop {
+            let running = query_data
+                .fetch
+                .borrow()
+                .as_ref()
+                .map(|fetch| (fetch.trigger, fetch.notifier.clone()));
+            match running {
+                Some((running_trigger, notifier)) if running_trigger != trigger => {
+                    notifier.notified_owned().await;
+                }
+                _ => break,
+            }
+        }
+
+        Self::fetch(query, query_data, RefetchMode::Join, trigger).await
+    }
+
+    /// Fetch the query and wait until it settles.
+    async fn fetch(
+        query: &Query<Q>,
+        query_data: &QueryData<Q>,
+        mode: RefetchMode,
+        trigger: QueryTrigger,
+    ) {
+        match mode {
+            RefetchMode::Join => {}
+            RefetchMode::Queue => {
+                // Wait for the running fetch to finish before starting a new one
+                let running = query_data
+                    .fetch
+                    .borrow()
+                    .as_ref()
+                    .map(|fetch| fetch.notifier.clone().notified_owned());
+                if let Some(running) = running {
+                    running.await;
+                }
+            }
+            RefetchMode::Cancel => {
+                // Those waiting for the cancelled fetch will now wait for the new one
+                if let Some(fetch) = query_data.fetch.take() {
+                    fetch.task.cancel();
+                    fetch.cancel_signal.cancel();
+                    Self::start_fetch(
+                        query,
+                        query_data,
+                        trigger,
+                        fetch.notifier,
+                        fetch.settlement_instant,
+                    );
+                }
+            }
+        }
+
+        let running = query_data
+            .fetch
+            .borrow()
+            .as_ref()
+            .map(|fetch| fetch.notifier.clone());
+        let notifier = match running {
+            Some(notifier) => notifier,
+            None => {
+                let notifier = Arc::new(Notify::new());
+                let settlement_instant = query_data.state.borrow().settlement_instant();
+                Self::start_fetch(
+                    query,
+                    query_data,
+                    trigger,
+                    notifier.clone(),
+                    settlement_instant,
+                );
+                notifier
+            }
+        };
+        notifier.notified_owned().await;
+    }
+
+    /// Spawn a new fetch of the query, `notifier` will be notified once it settles.
+    fn start_fetch(
+        query: &Query<Q>,
+        query_data: &QueryData<Q>,
+        trigger: QueryTrigger,
+        notifier: Arc<Notify>,
+        settlement_instant: Option<Instant>,
+    ) {
+        // Set to Loading, unless the query is settled and uses structural sharing,
+        // in which case it is refetched in the background to avoid spurious re-renders
+        let is_settled = matches!(*query_data.state.borrow(), QueryStateData::Settled { .. });
+        if !(is_settled && query.structural_sharing.is_some()) {
+            let res = mem::replace(&mut *query_data.state.borrow_mut(), QueryStateData::Pending)
+                .into_loading();
+            *query_data.state.borrow_mut() = res;
+            for reactive_context in query_data.reactive_contexts.lock().unwrap().iter() {
+                reactive_context.mark_dirty();
+            }
+        }
+
+        let generation = query_data.generation.get() + 1;
+        query_data.generation.set(generation);
+
+        let cancel_signal = CancelSignal::default();
+        let task = spawn_forever({
+            let query = query.clone();
+            let query_data = query_data.clone();
+            let notifier = notifier.clone();
+            let cancel_signal = cancel_signal.clone();
+            let fetch = async move {
+                trace_event!("Fetch started");
+                #[cfg(feature = "tracing")]
+                let start = Instant::now();
+
+                // Run
+                let res = Self::run(&query, &query_data, trigger, cancel_signal).await;
+
+                trace_event!(
+                    duration_ms = start.elapsed().as_millis() as u64,
+                    ok = res.is_ok(),
+                    "Fetch finished"
+                );
+
+                if query_data.generation.get() == generation {
+                    Self::settle(&query, &query_data, res);
+                } else {
+                    // Discard the result as the data was modified meanwhile
+                    trace_event!("Fetch result discarded");
+                    query.query.on_discarded(&query.keys, &res);
+                }
+
+                // Let those waiting for this fetch know it finished, even if its result was discarded
+                query_data.fetch.borrow_mut().take();
+                notifier.notify_waiters();
+
+                // Notify the suspense task if any
+                if let Some(suspense_task) = &*query_data.suspense_task.borrow() {
+                    suspense_task.notifier.notify_waiters();
+                };
+            };
+            trace_span!(
+                fetch,
+                "query_fetch",
+                query = std::any::type_name::<Q>(),
+                trigger = ?trigger,
+                generation
+            )
+        });
+        *query_data.fetch.borrow_mut() = Some(QueryFetch {
+            task,
+            notifier,
+            cancel_signal,
+            trigger,
+            settlement_instant,
+        });
+    }
+
+    /// Set the query to settled with the given result.
+    fn settle(query: &Query<Q>, query_data: &QueryData<Q>, res: Result<Q::Ok, Q::Err>) {
+        QueryClient::current().mark_changed();
+
+        // Keep the current value if it is settled and equal to the new one,
+        // subscribers already see it so there is no need to notify them
+        if let Some(eq) = &query.structural_sharing {
+            if let (
+                QueryStateData::Settled {
+                    res: Ok(current),
+                    settlement_instant,
+                },
+                Ok(new),
+            ) = (&mut *query_data.state.borrow_mut(), &res)
+            {
+                if eq(current, new) {
+                    *settlement_instant = QueryClock::current().now();
+                    return;
+                }
+            }
+        }
+
+        // Set to settled
+        *query_data.state.borrow_mut() = QueryStateData::Settled {
+            res,
+            settlement_instant: QueryClock::current().now(),
+        };
+        for reactive_context in query_data.reactive_contexts.lock().unwrap().iter() {
+            reactive_context.mark_dirty();
+        }
+    }
+
+    /// Cancel the running execution of the query, if any, and restore its previous state.
+    fn cancel_fetch(query_data: &QueryData<Q>) {
+        let Some(fetch) = query_data.fetch.take() else {
+            return;
+        };
+        trace_event!(query = std::any::type_name::<Q>(), "Fetch cancelled");
+        fetch.task.cancel();
+        fetch.cancel_signal.cancel();
+
+        // Set back to the previous state
+        let res = mem::replace(&mut *query_data.state.borrow_mut(), QueryStateData::Pending)
+            .into_previous(fetch.settlement_instant);
+        *query_data.state.borrow_mut() = res;
+        for reactive_context in query_data.reactive_contexts.lock().unwrap().iter() {
+            reactive_context.mark_dirty();
+        }
+
+        // Let those waiting for this fetch know it finished
+        fetch.notifier.notify_waiters();
+    }
+
+    /// Wait until the app is online, setting the query to [QueryStateData::Paused] meanwhile.
+    async fn pause(query_data: &QueryData<Q>, online_manager: OnlineManager) {
+        if online_manager.peek_online() {
+            return;
+        }
+
+        let res = mem::replace(&mut *query_data.state.borrow_mut(), QueryStateData::Pending)
+            .into_paused();
+        *query_data.state.borrow_mut() = res;
+        for reactive_context in query_data.reactive_contexts.lock().unwrap().iter() {
+            reactive_context.mark_dirty();
+        }
+
+        trace_event!("Fetch paused while offline");
+        query_data.wait(online_manager.wait_online()).await;
+        trace_event!("Fetch resumed");
+
+        let res = mem::replace(&mut *query_data.state.borrow_mut(), QueryStateData::Pending)
+            .into_loading();
+        *query_data.state.borrow_mut() = res;
+        for reactive_context in query_data.reactive_contexts.lock().unwrap().iter() {
+            reactive_context.mark_dirty();
+        }
+    }
+
+    /// Run the query once, with its mock if any.
+    async fn run_query(&self, query: &Query<Q>, ctx: &QueryContext<Q>) -> Result<Q::Ok, Q::Err> {
+        #[cfg(feature = "testing")]
+        if let Some(mock) = self.mock.peek().clone() {
+            return mock(&query.keys);
+        }
+        query.query.run_with_context(&query.keys, ctx).await
+    }
+
+    /// Run the query, retrying it as configured in its [QueryRetry].
+    async fn run(
+        query: &Query<Q>,
+        query_data: &QueryData<Q>,
+        trigger: QueryTrigger,
+        cancel_signal: CancelSignal,
+    ) -> Result<Q::Ok, Q::Err> {
+        let online_manager = OnlineManager::current();
+        let storage = Self::current();
+        let mut ctx = QueryContext {
+            attempt: 0,
+            trigger,
+            cancel_signal,
+            state: query_data.state.clone(),
+        };
+        let mut has_run = false;
+        #[cfg(feature = "testing")]
+        query_data.waiting.set(false);
+        loop {
+            // Wait to be online if necessary
+            if query.network_mode.waits_for_online(has_run) {
+                Self::pause(query_data, online_manager).await;
+            }
+            has_run = true;
+
+            let interceptors = storage.interceptors.peek().clone();
+            let res = match interceptors
+                .iter()
+                .find_map(|interceptor| interceptor.intercept(&query.keys))
+            {
+                Some(res) => res,
+                None => storage.run_query(query, &ctx).await,
+            };
+            for interceptor in &interceptors {
+                interceptor.ran(&query.keys, &res);
+            }
+
+            match res {
+                // Failed because it went offline, try again once back online
+                Err(_)
+                    if query.network_mode != NetworkMode::Always
+                        && !online_manager.peek_online() => {}
+                Err(err) if query.retry.should_retry(ctx.attempt, &err) => {
+                    trace_event!(attempt = ctx.attempt, "Fetch failed, retrying");
+                    query_data
+                        .wait(QueryClock::current().sleep(query.retry.backoff(ctx.attempt)))
+                        .await;
+                    ctx.attempt += 1;
+                }
+                res => {
+                    for interceptor in &interceptors {
+                        interceptor.settled(&query.keys, &res);
+                    }
+                    return res;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl<Q: QueryCapability> QueriesStorage<Q> {
+    /// Run the given function instead of [QueryCapability::run] for every query of this type,
+    /// e.g to test the UI with canned data. Replaces the previous mock, if any.
+    pub fn mock(run: impl Fn(&Q::Keys) -> Result<Q::Ok, Q::Err> + 'static) {
+        let mut mock = Self::current().mock;
+        *mock.write() = Some(Rc::new(run));
+    }
+
+    /// Go back to running the queries of this type with [QueryCapability::run].
+    pub fn unmock() {
+        let mut mock = Self::current().mock;
+        *mock.write() = None;
+    }
+
+    /// Run the given function with the state of a query with the given keys, if any.
+    pub(crate) fn with_state<T>(
+        &self,
+        keys: &Q::Keys,
+        f: impl FnOnce(&QueryStateData<Q>) -> T,
+    ) -> Option<T> {
+        let storage = self.storage.peek();
+        let (_, query_data) = storage.iter().find(|(query, _)| query.keys == *keys)?;
+        let state = query_data.state.borrow();
+        Some(f(&state))
+    }
+
+    /// The notifiers of the queries of this type that are running and not just waiting for the clock or to be online.
+    pub(crate) fn busy_fetches(&self) -> Vec<Arc<Notify>> {
+        self.storage
+            .peek()
+            .values()
+            .filter(|query_data| !query_data.waiting.get())
+            .filter_map(|query_data| {
+                let fetch = query_data.fetch.borrow();
+                fetch.as_ref().map(|fetch| fetch.notifier.clone())
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "devtools")]
+impl<Q: QueryCapability> QueriesStorage<Q> {
+    pub(crate) fn set_debug(&self, debug: crate::devtools::DebugQuery<Q>) {
+        let mut debug_value = self.debug;
+        debug_value.set(Some(debug));
+    }
+
+    /// Describe every query of this type.
+    pub(crate) fn entries(&self) -> Vec<crate::devtools::QueryEntry> {
+        use std::hash::{DefaultHasher, Hasher};
+
+        let debug = *self.debug.peek();
+        self.storage
+            .peek()
+            .iter()
+            .map(|(query, query_data)| {
+                let state = query_data.state.borrow();
+                let keys = match debug {
+                    Some(debug) => (debug.keys)(&query.keys),
+                    None => {
+                        let mut hasher = DefaultHasher::new();
+                        query.keys.hash(&mut hasher);
+                        format!("#{:x}", hasher.finish())
+                    }
+                };
+                let state_name = match debug {
+                    Some(debug) => (debug.state)(&state),
+                    None => state.variant_name().to_string(),
+                };
+                let fetch = query_data.fetch.borrow();
+                let settlement_instant = match &*fetch {
+                    Some(fetch) => fetch.settlement_instant,
+                    None => state.settlement_instant(),
+                };
+                crate::devtools::QueryEntry {
+                    id: query_data.id(),
+                    type_id: std::any::TypeId::of::<Q>(),
+                    type_name: std::any::type_name::<Q>(),
+                    keys,
+                    state: state_name,
+                    subscribers: query_data.reactive_contexts.lock().unwrap().len(),
+                    settled_ago: settlement_instant
+                        .map(|instant| QueryClock::current().now().duration_since(instant)),
+                    is_fetching: fetch.is_some(),
+                    has_interval_task: query_data.interval_task.borrow().is_some(),
+                    has_clean_task: query_data.clean_task.borrow().is_some(),
+                }
+            })
+            .collect()
+    }
+
+    fn find_entry(&self, id: usize) -> Option<(Query<Q>, QueryData<Q>)> {
+        self.storage
+            .peek()
+            .iter()
+            .find(|(_, query_data)| query_data.id() == id)
+            .map(|(query, query_data)| (query.clone(), query_data.clone()))
+    }
+
+    /// Invalidate the query with the given [crate::devtools::QueryEntry::id].
+    pub(crate) async fn invalidate_entry(&self, id: usize) {
+        if let Some((query, query_data)) = self.find_entry(id) {
+            Self::refetch_queries(&[(&query, &query_data)]).await
+        }
+    }
+
+    /// Reset the query with the given [crate::devtools::QueryEntry::id], running it again if it's in use.
+    pub(crate) async fn reset_entry(&self, id: usize) {
+        if let Some((query, query_data)) = self.find_entry(id) {
+            query_data.reset(&query);
+            if query.enabled && query_data.has_subscribers() {
+                Self::run_queries(&[(&query, &query_data)], QueryTrigger::Invalidate).await
+            }
+        }
+    }
+
+    /// Remove the query with the given [crate::devtools::QueryEntry::id],
+    /// unless it still has subscribers, see [QueriesStorage::clear].
+    pub(crate) fn remove_entry(&self, id: usize) {
+        let Some((query, query_data)) = self.find_entry(id) else {
+            return;
+        };
+        if !query_data.has_subscribers() {
+            query_data.reset(&query);
+            query_data.cancel_tasks();
+            let mut storage = self.storage;
+            storage.write().remove(&query);
+        }
     }
+}
 
-    pub async fn invalidate_matching(matching_keys: Q::Keys) {
-        let storage = consume_context::<QueriesStorage<Q>>();
+/// What to do when a [Query] is invalidated while it is already running.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub enum RefetchMode {
+    /// Wait for the running execution instead of starting a new one.
+    #[default]
+    Join,
+    /// Wait for the running execution and then run it again.
+    /// Multiple queued refetches are merged into a single one.
+    Queue,
+    /// Cancel the running execution and start a new one.
+    Cancel,
+}
 
-        // Get those queries that match
-        let mut matching_queries = Vec::new();
-        for (query, data) in storage.storage.read().iter() {
-            if query.query.matches(&matching_keys) {
-                matching_queries.push((query.clone(), data.clone()));
-            }
+/// How a [Query] behaves while the app is offline, see [crate::online::OnlineManager].
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub enum NetworkMode {
+    /// Only run while online, the query is [QueryStateData::Paused] meanwhile.
+    /// Executions that fail while offline are paused and retried once back online.
+    #[default]
+    Online,
+    /// Run regardless of the connectivity, e.g for queries that don't use the network.
+    Always,
+    /// Run once regardless of the connectivity, e.g to hit a local cache,
+    /// but pause any further attempts while offline.
+    OfflineFirst,
+}
+
+impl NetworkMode {
+    /// Whether to wait to be online before running, given if the query already ran in this execution.
+    fn waits_for_online(&self, has_run: bool) -> bool {
+        match self {
+            NetworkMode::Online => true,
+            NetworkMode::Always => false,
+            NetworkMode::OfflineFirst => has_run,
         }
-        let matching_queries = matching_queries
-            .iter()
-            .map(|(q, d)| (q, d))
-            .collect::<Vec<_>>();
+    }
+}
 
-        // Invalidate the queries
-        Self::run_queries(&matching_queries).await
+/// Retry policy of a [Query], used when [QueryCapability::run] fails.
+///
+/// Between attempts the query waits an exponentially growing delay (with some jitter), and
+/// it stays [QueryStateData::Loading] until it either succeeds or runs out of attempts.
+pub struct QueryRetry<Q: QueryCapability> {
+    attempts: u32,
+    delay: Duration,
+    max_delay: Duration,
+    retry_if: Option<RetryIf<Q>>,
+}
+
+type RetryIf<Q> = Captured<Rc<dyn Fn(&<Q as QueryCapability>::Err) -> bool>>;
+
+impl<Q: QueryCapability> Clone for QueryRetry<Q> {
+    fn clone(&self) -> Self {
+        Self {
+            attempts: self.attempts,
+            delay: self.delay,
+            max_delay: self.max_delay,
+            retry_if: self.retry_if.clone(),
+        }
     }
+}
 
-    async fn run_queries(queries: &[(&Query<Q>, &QueryData<Q>)]) {
-        let tasks = FuturesUnordered::new();
+impl<Q: QueryCapability> PartialEq for QueryRetry<Q> {
+    fn eq(&self, other: &Self) -> bool {
+        self.attempts == other.attempts
+            && self.delay == other.delay
+            && self.max_delay == other.max_delay
+            && self.retry_if == other.retry_if
+    }
+}
 
-        for (query, query_data) in queries {
-            // Set to Loading
-            let res = mem::replace(&mut *query_data.state.borrow_mut(), QueryStateData::Pending)
-                .into_loading();
-            *query_data.state.borrow_mut() = res;
-            for reactive_context in query_data.reactive_contexts.lock().unwrap().iter() {
-                reactive_context.mark_dirty();
-            }
+impl<Q: QueryCapability> Default for QueryRetry<Q> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
 
-            tasks.push(Box::pin(async move {
-                // Run
-                let res = query.query.run(&query.keys).await;
+impl<Q: QueryCapability> QueryRetry<Q> {
+    /// Retry a failed query up to `attempts` times.
+    pub fn new(attempts: u32) -> Self {
+        Self {
+            attempts,
+            delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            retry_if: None,
+        }
+    }
 
-                // Set to settled
-                *query_data.state.borrow_mut() = QueryStateData::Settled {
-                    res,
-                    settlement_instant: Instant::now(),
-                };
-                for reactive_context in query_data.reactive_contexts.lock().unwrap().iter() {
-                    reactive_context.mark_dirty();
-                }
+    /// Delay before the first retry, it doubles on every following attempt.
+    ///
+    /// Defaults to `1s`.
+    pub fn delay(self, delay: Duration) -> Self {
+        Self { delay, ..self }
+    }
 
-                // Notify the suspense task if any
-                if let Some(suspense_task) = &*query_data.suspense_task.borrow() {
-                    suspense_task.notifier.notify_waiters();
-                };
-            }));
+    /// Upper limit for the delay between attempts.
+    ///
+    /// Defaults to `30s`.
+    pub fn max_delay(self, max_delay: Duration) -> Self {
+        Self { max_delay, ..self }
+    }
+
+    /// Only retry those errors for which `retry_if` returns `true`.
+    ///
+    /// By default every error is retried.
+    ///
+    /// **Note**: Just like [Captured], changing this function will not be noticed by the query.
+    pub fn retry_if(self, retry_if: impl Fn(&Q::Err) -> bool + 'static) -> Self {
+        Self {
+            retry_if: Some(Captured(Rc::new(retry_if))),
+            ..self
         }
+    }
 
-        tasks.count().await;
+    fn should_retry(&self, attempt: u32, err: &Q::Err) -> bool {
+        attempt < self.attempts
+            && self
+                .retry_if
+                .as_ref()
+                .map(|retry_if| retry_if(err))
+                .unwrap_or(true)
+    }
+
+    /// Exponential backoff with jitter, so the wait is somewhere between half and the full delay.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let random = RandomState::new().hash_one(attempt) as f64 / u64::MAX as f64;
+        delay / 2 + (delay / 2).mul_f64(random)
     }
 }
 
@@ -409,6 +1671,7 @@ pub struct GetQuery<Q: QueryCapability> {
 
     stale_time: Duration,
     clean_time: Duration,
+    retry: QueryRetry<Q>,
 }
 
 impl<Q: QueryCapability> GetQuery<Q> {
@@ -418,6 +1681,7 @@ impl<Q: QueryCapability> GetQuery<Q> {
             keys,
             stale_time: Duration::ZERO,
             clean_time: Duration::ZERO,
+            retry: QueryRetry::default(),
         }
     }
     /// For how long is the data considered stale. If a query subscriber is mounted and the data is stale, it will re run the query.
@@ -433,6 +1697,13 @@ impl<Q: QueryCapability> GetQuery<Q> {
     pub fn clean_time(self, clean_time: Duration) -> Self {
         Self { clean_time, ..self }
     }
+
+    /// How to retry the query when it fails.
+    ///
+    /// Defaults to no retries.
+    pub fn retry(self, retry: QueryRetry<Q>) -> Self {
+        Self { retry, ..self }
+    }
 }
 
 impl<Q: QueryCapability> From<GetQuery<Q>> for Query<Q> {
@@ -446,10 +1717,21 @@ impl<Q: QueryCapability> From<GetQuery<Q>> for Query<Q> {
             stale_time: value.stale_time,
             clean_time: value.clean_time,
             interval_time: Duration::MAX,
+
+            retry: value.retry,
+            refetch_mode: RefetchMode::default(),
+
+            keep_previous_data: None,
+            initial_data: None,
+            placeholder_data: None,
+            structural_sharing: None,
+            refetch_on_focus: false,
+            refetch_on_reconnect: false,
+            network_mode: NetworkMode::default(),
         }
     }
 }
-#[derive(PartialEq, Clone)]
+#[derive(Clone)]
 pub struct Query<Q: QueryCapability> {
     query: Q,
     keys: Q::Keys,
@@ -459,6 +1741,49 @@ pub struct Query<Q: QueryCapability> {
     stale_time: Duration,
     clean_time: Duration,
     interval_time: Duration,
+
+    retry: QueryRetry<Q>,
+    refetch_mode: RefetchMode,
+
+    keep_previous_data: Option<Captured<CloneResult<Q>>>,
+    initial_data: Option<(InitialData<Q>, Instant)>,
+    placeholder_data: Option<PlaceholderData<Q>>,
+    structural_sharing: Option<Captured<EqValue<Q>>>,
+
+    refetch_on_focus: bool,
+    refetch_on_reconnect: bool,
+    network_mode: NetworkMode,
+}
+
+type EqValue<Q> = fn(&<Q as QueryCapability>::Ok, &<Q as QueryCapability>::Ok) -> bool;
+
+type InitialData<Q> = Captured<Rc<dyn Fn() -> <Q as QueryCapability>::Ok>>;
+
+type PlaceholderData<Q> =
+    Captured<Rc<dyn Fn(&<Q as QueryCapability>::Keys) -> <Q as QueryCapability>::Ok>>;
+
+type CloneResult<Q> = fn(
+    &Result<<Q as QueryCapability>::Ok, <Q as QueryCapability>::Err>,
+) -> Result<<Q as QueryCapability>::Ok, <Q as QueryCapability>::Err>;
+
+// The retry policy and the initial data are intentionally left out so subscribers with different ones share the same entry,
+// see [Query::retry] and [Query::initial_data]
+impl<Q: QueryCapability> PartialEq for Query<Q> {
+    fn eq(&self, other: &Self) -> bool {
+        self.query == other.query
+            && self.keys == other.keys
+            && self.enabled == other.enabled
+            && self.stale_time == other.stale_time
+            && self.clean_time == other.clean_time
+            && self.interval_time == other.interval_time
+            && self.refetch_mode == other.refetch_mode
+            && self.keep_previous_data == other.keep_previous_data
+            && self.placeholder_data == other.placeholder_data
+            && self.structural_sharing == other.structural_sharing
+            && self.refetch_on_focus == other.refetch_on_focus
+            && self.refetch_on_reconnect == other.refetch_on_reconnect
+            && self.network_mode == other.network_mode
+    }
 }
 
 impl<Q: QueryCapability> Eq for Query<Q> {}
@@ -486,6 +1811,15 @@ impl<Q: QueryCapability> Query<Q> {
             stale_time: Duration::ZERO,
             clean_time: Duration::from_secs(5 * 60),
             interval_time: Duration::MAX,
+            retry: QueryRetry::default(),
+            refetch_mode: RefetchMode::default(),
+            keep_previous_data: None,
+            initial_data: None,
+            placeholder_data: None,
+            structural_sharing: None,
+            refetch_on_focus: false,
+            refetch_on_reconnect: false,
+            network_mode: NetworkMode::default(),
         }
     }
 
@@ -522,30 +1856,180 @@ impl<Q: QueryCapability> Query<Q> {
             ..self
         }
     }
+
+    /// How to retry the query when it fails.
+    ///
+    /// Defaults to no retries.
+    ///
+    /// **Note**: The retry policy is not part of the identity of the query, so subscribers with different policies share the same cached value.
+    /// Each execution is retried with the policy of the subscriber that started it, those that join it while running don't change it.
+    /// Just like [Captured], changing only the policy of a mounted subscriber will not be noticed.
+    pub fn retry(self, retry: QueryRetry<Q>) -> Self {
+        Self { retry, ..self }
+    }
+
+    /// What to do when this query is invalidated while it is already running.
+    /// Mounting new subscribers or interval re-executions always join the running execution.
+    ///
+    /// Defaults to [RefetchMode::Join].
+    pub fn refetch_mode(self, refetch_mode: RefetchMode) -> Self {
+        Self {
+            refetch_mode,
+            ..self
+        }
+    }
+
+    /// Keep showing the value of the previous keys as a placeholder while the new keys load, see [QueryReader::placeholder],
+    /// instead of going back to [QueryStateData::Pending]. Useful for e.g paginated tables.
+    ///
+    /// Defaults to `false`.
+    pub fn keep_previous_data(self, keep_previous_data: bool) -> Self
+    where
+        Q::Ok: Clone,
+        Q::Err: Clone,
+    {
+        Self {
+            keep_previous_data: keep_previous_data
+                .then_some(Captured(Result::clone as CloneResult<Q>)),
+            ..self
+        }
+    }
+
+    /// Seed the cache with this value when the query is not cached yet, as if it had settled at `updated_at`,
+    /// e.g [QueryClock::now] for a value that was just fetched.
+    /// It will be refreshed once it becomes stale, just like any other value.
+    ///
+    /// Defaults to no initial data.
+    ///
+    /// **Note**: Only the first subscriber to create the entry seeds it, so changing this value will not be noticed by the query.
+    pub fn initial_data(self, initial_data: Q::Ok, updated_at: Instant) -> Self
+    where
+        Q::Ok: Clone,
+    {
+        Self {
+            initial_data: Some((Captured(Rc::new(move || initial_data.clone())), updated_at)),
+            ..self
+        }
+    }
+
+    /// Show the value returned by `placeholder_data` as a placeholder while the query loads for the first time, see [QueryReader::placeholder].
+    /// Unlike [Query::initial_data], this value is never cached.
+    ///
+    /// Defaults to no placeholder data.
+    ///
+    /// **Note**: Just like [Captured], changing this function will not be noticed by the query.
+    pub fn placeholder_data(self, placeholder_data: impl Fn(&Q::Keys) -> Q::Ok + 'static) -> Self {
+        Self {
+            placeholder_data: Some(Captured(Rc::new(placeholder_data))),
+            ..self
+        }
+    }
+
+    /// Keep the current value when the query is refetched and the new value is equal,
+    /// so subscribers are not notified. Settled queries are also refetched in the background,
+    /// meaning they stay [QueryStateData::Settled] while running.
+    ///
+    /// Useful to avoid re-rendering on every [Query::interval_time] when the value barely changes.
+    ///
+    /// Defaults to `false`, in which case every fetch goes through [QueryStateData::Loading].
+    pub fn structural_sharing(self, structural_sharing: bool) -> Self
+    where
+        Q::Ok: PartialEq,
+    {
+        Self {
+            structural_sharing: structural_sharing
+                .then_some(Captured(<Q::Ok as PartialEq>::eq as EqValue<Q>)),
+            ..self
+        }
+    }
+
+    /// Rerun this query when the app regains focus, if it is stale and has subscribers.
+    /// The focus is tracked by the [crate::focus::FocusManager].
+    ///
+    /// Defaults to `false`.
+    pub fn refetch_on_focus(self, refetch_on_focus: bool) -> Self {
+        Self {
+            refetch_on_focus,
+            ..self
+        }
+    }
+
+    /// Rerun this query when the app goes back online, if it is stale and has subscribers.
+    /// The connectivity is tracked by the [crate::online::OnlineManager].
+    ///
+    /// Defaults to `false`.
+    pub fn refetch_on_reconnect(self, refetch_on_reconnect: bool) -> Self {
+        Self {
+            refetch_on_reconnect,
+            ..self
+        }
+    }
+
+    /// How this query behaves while the app is offline.
+    ///
+    /// Defaults to [NetworkMode::Online].
+    pub fn network_mode(self, network_mode: NetworkMode) -> Self {
+        Self {
+            network_mode,
+            ..self
+        }
+    }
+}
+
+/// Where the placeholder shown while a query loads comes from, see [QueryReader::placeholder].
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum QueryPlaceholder {
+    /// The value of the previous keys, see [Query::keep_previous_data].
+    PreviousData,
+    /// The value given by [Query::placeholder_data].
+    PlaceholderData,
 }
 
 pub struct QueryReader<Q: QueryCapability> {
     state: Rc<RefCell<QueryStateData<Q>>>,
+    placeholder: Option<QueryPlaceholder>,
 }
 
 impl<Q: QueryCapability> QueryReader<Q> {
-    pub fn state(&self) -> Ref<QueryStateData<Q>> {
+    pub fn state(&self) -> Ref<'_, QueryStateData<Q>> {
         self.state.borrow()
     }
 
+    /// Check if the state is a placeholder shown as [QueryStateData::Loading] while the query loads, which is never cached.
+    ///
+    /// See [QueryReader::placeholder] to know where it comes from.
+    pub fn is_placeholder(&self) -> bool {
+        self.placeholder.is_some()
+    }
+
+    /// Where the placeholder comes from, if the state is one, see [QueryReader::is_placeholder].
+    pub fn placeholder(&self) -> Option<QueryPlaceholder> {
+        self.placeholder
+    }
+
     /// Get the result of the query.
     ///
     /// **This method will panic if the query is not settled.**
-    pub fn as_settled(&self) -> Ref<Result<Q::Ok, Q::Err>> {
-        Ref::map(self.state.borrow(), |state| match state {
-            QueryStateData::Settled { res, .. } => res,
-            _ => panic!("Query is not settled."),
+    pub fn as_settled(&self) -> Ref<'_, Result<Q::Ok, Q::Err>> {
+        self.try_as_settled().expect("Query is not settled.")
+    }
+
+    /// Get the result of the query, or [QueryError::NotSettled] if it's not settled.
+    pub fn try_as_settled(&self) -> Result<Ref<'_, QueryResult<Q>>, QueryError> {
+        Ref::filter_map(self.state.borrow(), |state| match state {
+            QueryStateData::Settled { res, .. } => Some(res),
+            _ => None,
         })
+        .map_err(|_| QueryError::NotSettled)
     }
 }
 
+/// A placeholder state and where it comes from.
+type PlaceholderState<Q> = (Rc<RefCell<QueryStateData<Q>>>, QueryPlaceholder);
+
 pub struct UseQuery<Q: QueryCapability> {
     query: Signal<Query<Q>>,
+    placeholder: CopyValue<Option<PlaceholderState<Q>>>,
 }
 
 impl<Q: QueryCapability> Clone for UseQuery<Q> {
@@ -557,27 +2041,40 @@ impl<Q: QueryCapability> Clone for UseQuery<Q> {
 impl<Q: QueryCapability> Copy for UseQuery<Q> {}
 
 impl<Q: QueryCapability> UseQuery<Q> {
+    /// Get a future that runs this query for the given trigger and resolves with its result.
+    pub(crate) fn try_run_for_trigger(
+        &self,
+        trigger: QueryTrigger,
+    ) -> Result<impl Future<Output = ()> + 'static, QueryError> {
+        let query = self.query.peek().clone();
+        let query_data = self.try_query_data()?;
+
+        Ok(async move { QueriesStorage::run_for_trigger(&query, &query_data, trigger).await })
+    }
+
+    /// The [QueryCapability] of this query.
+    pub(crate) fn peek_capability(&self) -> Q {
+        self.query.peek().query.clone()
+    }
+
     /// Read the [Query] state.
     ///
     /// This **will** automatically subscribe.
     /// If you want a **non-subscribing** method have a look at [UseQuery::peek].
     pub fn read(&self) -> QueryReader<Q> {
-        let storage = consume_context::<QueriesStorage<Q>>();
-        let query_data = storage
-            .storage
-            .peek_unchecked()
-            .get(&self.query.peek())
-            .cloned()
-            .unwrap();
+        self.try_read().unwrap()
+    }
+
+    /// Read the [Query] state, see [UseQuery::read].
+    pub fn try_read(&self) -> Result<QueryReader<Q>, QueryError> {
+        let query_data = self.try_query_data()?;
 
         // Subscribe if possible
         if let Some(reactive_context) = ReactiveContext::current() {
             reactive_context.subscribe(query_data.reactive_contexts);
         }
 
-        QueryReader {
-            state: query_data.state,
-        }
+        Ok(self.reader(query_data.state))
     }
 
     /// Read the [Query] state.
@@ -585,16 +2082,75 @@ impl<Q: QueryCapability> UseQuery<Q> {
     /// This **will not** automatically subscribe.
     /// If you want a **subscribing** method have a look at [UseQuery::read].
     pub fn peek(&self) -> QueryReader<Q> {
-        let storage = consume_context::<QueriesStorage<Q>>();
-        let query_data = storage
-            .storage
-            .peek_unchecked()
-            .get(&self.query.peek())
-            .cloned()
-            .unwrap();
+        self.try_peek().unwrap()
+    }
 
-        QueryReader {
-            state: query_data.state,
+    /// Read the [Query] state without subscribing, see [UseQuery::peek].
+    pub fn try_peek(&self) -> Result<QueryReader<Q>, QueryError> {
+        let query_data = self.try_query_data()?;
+
+        Ok(self.reader(query_data.state))
+    }
+
+    /// Get the cached entry of this query, creating it again if it was evicted.
+    fn try_query_data(&self) -> Result<QueryData<Q>, QueryError> {
+        let mut storage = Runtime::try_current()
+            .and_then(|_| try_consume_context::<QueriesStorage<Q>>())
+            .ok_or(QueryError::MissingContext)?;
+
+        let query = self.query.peek().clone();
+        let query_data = storage.storage.peek_unchecked().get(&query).cloned();
+        if let Some(query_data) = query_data {
+            return Ok(query_data);
+        }
+
+        // The entry was evicted while still in use, e.g by its clean task racing with a render
+        trace_event!(query = std::any::type_name::<Q>(), "Query entry recreated");
+        let query_data = storage.insert_or_get_query(query.clone());
+        if query.enabled && query_data.state.borrow().is_stale(&query) {
+            let query_data = query_data.clone();
+            spawn(async move {
+                QueriesStorage::run_queries(&[(&query, &query_data)], QueryTrigger::Mount).await;
+            });
+        }
+        Ok(query_data)
+    }
+
+    /// Read the placeholder instead of the cached state while the latter has no value.
+    fn reader(&self, state: Rc<RefCell<QueryStateData<Q>>>) -> QueryReader<Q> {
+        let mut placeholder = self.placeholder;
+        if state.borrow().value().is_some() {
+            if placeholder.peek().is_some() {
+                placeholder.set(None);
+            }
+            return QueryReader {
+                state,
+                placeholder: None,
+            };
+        }
+
+        if placeholder.peek().is_none() {
+            let query = self.query.peek();
+            if let Some(placeholder_data) = &query.placeholder_data {
+                placeholder.set(Some((
+                    Rc::new(RefCell::new(QueryStateData::Loading {
+                        res: Some(Ok(placeholder_data(&query.keys))),
+                    })),
+                    QueryPlaceholder::PlaceholderData,
+                )));
+            }
+        }
+
+        let placeholder = placeholder.peek().clone();
+        match placeholder {
+            Some((state, kind)) => QueryReader {
+                state,
+                placeholder: Some(kind),
+            },
+            None => QueryReader {
+                state,
+                placeholder: None,
+            },
         }
     }
 
@@ -606,9 +2162,16 @@ impl<Q: QueryCapability> UseQuery<Q> {
         Q::Ok: Clone,
         Q::Err: Clone,
     {
-        let storage = consume_context::<QueriesStorage<Q>>();
-        let mut storage = storage.storage.write_unchecked();
-        let query_data = storage.get_mut(&self.query.peek()).unwrap();
+        self.try_suspend().unwrap()
+    }
+
+    /// Suspend this query until it has been **settled**, see [UseQuery::suspend].
+    pub fn try_suspend(&self) -> Result<Result<QueryResult<Q>, RenderError>, QueryError>
+    where
+        Q::Ok: Clone,
+        Q::Err: Clone,
+    {
+        let query_data = self.try_query_data()?;
 
         // Subscribe if possible
         if let Some(reactive_context) = ReactiveContext::current() {
@@ -616,8 +2179,10 @@ impl<Q: QueryCapability> UseQuery<Q> {
         }
 
         let state = &*query_data.state.borrow();
-        match state {
-            QueryStateData::Pending | QueryStateData::Loading { res: None } => {
+        Ok(match state {
+            QueryStateData::Pending
+            | QueryStateData::Loading { res: None }
+            | QueryStateData::Paused { res: None } => {
                 let suspense_task_clone = query_data.suspense_task.clone();
                 let mut suspense_task = query_data.suspense_task.borrow_mut();
                 let QuerySuspenseData { task, .. } = suspense_task.get_or_insert_with(|| {
@@ -633,50 +2198,66 @@ impl<Q: QueryCapability> UseQuery<Q> {
                 });
                 Err(RenderError::Suspended(SuspendedFuture::new(*task)))
             }
-            QueryStateData::Settled { res, .. } | QueryStateData::Loading { res: Some(res) } => {
-                Ok(res.clone())
-            }
-        }
+            QueryStateData::Settled { res, .. }
+            | QueryStateData::Loading { res: Some(res) }
+            | QueryStateData::Paused { res: Some(res) } => Ok(res.clone()),
+        })
     }
 
     /// Invalidate this query and await its result.
     ///
     /// For a `sync` version use [UseQuery::invalidate].
     pub async fn invalidate_async(&self) -> QueryReader<Q> {
-        let storage = consume_context::<QueriesStorage<Q>>();
+        self.try_invalidate_async().await.unwrap()
+    }
 
+    /// Invalidate this query and await its result, see [UseQuery::invalidate_async].
+    pub async fn try_invalidate_async(&self) -> Result<QueryReader<Q>, QueryError> {
         let query = self.query.peek().clone();
-        let query_data = storage
-            .storage
-            .peek_unchecked()
-            .get(&query)
-            .cloned()
-            .unwrap();
+        let query_data = self.try_query_data()?;
 
         // Run the query
-        QueriesStorage::run_queries(&[(&query, &query_data)]).await;
+        QueriesStorage::refetch_queries(&[(&query, &query_data)]).await;
 
-        QueryReader {
+        Ok(QueryReader {
             state: query_data.state.clone(),
-        }
+            placeholder: None,
+        })
     }
 
     /// Invalidate this query in the background.
     ///
     /// For an `async` version use [UseQuery::invalidate_async].
     pub fn invalidate(&self) {
-        let storage = consume_context::<QueriesStorage<Q>>();
+        self.try_invalidate().unwrap()
+    }
 
+    /// Invalidate this query in the background, see [UseQuery::invalidate].
+    pub fn try_invalidate(&self) -> Result<(), QueryError> {
         let query = self.query.peek().clone();
-        let query_data = storage
-            .storage
-            .peek_unchecked()
-            .get(&query)
-            .cloned()
-            .unwrap();
+        let query_data = self.try_query_data()?;
 
         // Run the query
-        spawn(async move { QueriesStorage::run_queries(&[(&query, &query_data)]).await });
+        spawn(async move { QueriesStorage::refetch_queries(&[(&query, &query_data)]).await });
+
+        Ok(())
+    }
+
+    /// Cancel the running execution of this query, if any.
+    ///
+    /// The query goes back to the state it had before it started running.
+    pub fn cancel(&self) {
+        self.try_cancel().unwrap()
+    }
+
+    /// Cancel the running execution of this query, see [UseQuery::cancel].
+    pub fn try_cancel(&self) -> Result<(), QueryError> {
+        let query_data = self.try_query_data()?;
+
+        // Cancel the query
+        QueriesStorage::cancel_fetch(&query_data);
+
+        Ok(())
     }
 }
 
@@ -707,25 +2288,65 @@ impl<Q: QueryCapability> UseQuery<Q> {
 /// By default it never refreshes automatically.
 ///
 /// See [Query::interval_time].
+///
+/// ### Retry
+/// How many times and how often should a failed query be retried before settling with the error.
+/// By default it is not retried.
+///
+/// See [Query::retry].
 pub fn use_query<Q: QueryCapability>(query: Query<Q>) -> UseQuery<Q> {
-    let mut storage = match try_consume_context::<QueriesStorage<Q>>() {
-        Some(storage) => storage,
-        None => provide_root_context(QueriesStorage::<Q>::new_in_root()),
-    };
+    let mut storage = QueriesStorage::<Q>::current();
+
+    let mut placeholder = use_hook(|| CopyValue::new(None));
 
     let mut make_query = |query: &Query<Q>, mut prev_query: Option<Query<Q>>| {
         let query_data = storage.insert_or_get_query(query.clone());
 
+        // Keep the value of the previous keys as placeholder while the new keys load
+        if let Some(prev_query) = &prev_query {
+            match &query.keep_previous_data {
+                Some(clone_result) if prev_query.keys != query.keys => {
+                    let prev_query_data = storage.storage.peek_unchecked().get(prev_query).cloned();
+                    let res = prev_query_data.as_ref().and_then(|prev_query_data| {
+                        prev_query_data.state.borrow().value().map(clone_result.0)
+                    });
+                    // If the previous keys had no value either just keep the current placeholder
+                    if let Some(res) = res {
+                        placeholder.set(Some((
+                            Rc::new(RefCell::new(QueryStateData::Loading { res: Some(res) })),
+                            QueryPlaceholder::PreviousData,
+                        )));
+                    }
+                }
+                Some(_) => {}
+                None => placeholder.set(None),
+            }
+        }
+
         // Update the query tasks if there has been a change in the query
         if let Some(prev_query) = prev_query.take() {
             storage.update_tasks(prev_query);
         }
 
-        // Immediately run the query if enabled and the value is stale
-        if query.enabled && query_data.state.borrow().is_stale(query) {
+        // Immediately run the query if enabled and the value is stale,
+        // unless it was just hydrated, in which case it's only skipped for this render
+        let hydrated = query_data.hydrated.get();
+        if hydrated {
+            let hydrated = query_data.hydrated.clone();
+            queue_effect(move || hydrated.set(false));
+        }
+        let is_stale = query_data.state.borrow().is_stale(query);
+        trace_event!(
+            query = std::any::type_name::<Q>(),
+            enabled = query.enabled,
+            is_stale,
+            hydrated,
+            "Query mounted"
+        );
+        if query.enabled && !hydrated && is_stale {
             let query = query.clone();
             spawn(async move {
-                QueriesStorage::run_queries(&[(&query, &query_data)]).await;
+                QueriesStorage::run_queries(&[(&query, &query_data)], QueryTrigger::Mount).await;
             });
         }
     };
@@ -749,5 +2370,110 @@ pub fn use_query<Q: QueryCapability>(query: Query<Q>) -> UseQuery<Q> {
 
     UseQuery {
         query: current_query,
+        placeholder,
+    }
+}
+
+/// Subscribe to a projection of the value of a [Query], so the component only re-renders when
+/// the value returned by `select` changes instead of on every change of the query state.
+///
+/// The selected value is [None] while the query has no [Result::Ok] value.
+pub fn use_query_select<Q: QueryCapability, T: PartialEq + 'static>(
+    query: Query<Q>,
+    select: impl Fn(&Q::Ok) -> T + 'static,
+) -> Memo<Option<T>> {
+    let query = use_query(query);
+
+    use_memo(move || {
+        // Subscribe to the query itself too, as its keys can change
+        query.query.read();
+        query.read().state().ok().map(&select)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::BuildHasher;
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Hash, Eq)]
+    struct Echo;
+
+    impl QueryCapability for Echo {
+        type Ok = u32;
+        type Err = u32;
+        type Keys = u32;
+
+        async fn run(&self, keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+            Ok(*keys)
+        }
+    }
+
+    #[test]
+    fn is_stale_at_the_given_instant() {
+        let query = Query::new(1, Echo).stale_time(Duration::from_secs(10));
+        let settlement_instant = Instant::now();
+        let state = QueryStateData::<Echo>::Settled {
+            res: Ok(1),
+            settlement_instant,
+        };
+        assert!(!state.is_stale_at(&query, settlement_instant));
+        assert!(!state.is_stale_at(&query, settlement_instant + Duration::from_secs(9)));
+        assert!(state.is_stale_at(&query, settlement_instant + Duration::from_secs(10)));
+        assert!(QueryStateData::<Echo>::Pending.is_stale_at(&query, settlement_instant));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_max_delay() {
+        let retry = QueryRetry::<Echo>::new(10)
+            .delay(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(1));
+        for (attempt, delay) in [
+            (0, 100),
+            (1, 200),
+            (2, 400),
+            (3, 800),
+            (4, 1000),
+            (30, 1000),
+        ] {
+            let delay = Duration::from_millis(delay);
+            for _ in 0..50 {
+                let backoff = retry.backoff(attempt);
+                assert!(
+                    backoff >= delay / 2 && backoff <= delay,
+                    "attempt {attempt} waited {backoff:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn backoff_is_jittered() {
+        let retry = QueryRetry::<Echo>::new(1);
+        let backoffs = (0..50).map(|_| retry.backoff(3)).collect::<HashSet<_>>();
+        assert!(backoffs.len() > 1);
+    }
+
+    #[test]
+    fn should_retry_until_out_of_attempts() {
+        let retry = QueryRetry::<Echo>::new(2);
+        assert!(retry.should_retry(0, &0));
+        assert!(retry.should_retry(1, &0));
+        assert!(!retry.should_retry(2, &0));
+
+        let retry = retry.retry_if(|err| *err == 0);
+        assert!(retry.should_retry(0, &0));
+        assert!(!retry.should_retry(0, &1));
+    }
+
+    #[test]
+    fn retry_is_not_part_of_the_query_identity() {
+        let query = Query::new(1, Echo);
+        let retried = Query::new(1, Echo).retry(QueryRetry::new(3));
+        assert!(query == retried);
+
+        let hasher = RandomState::new();
+        assert_eq!(hasher.hash_one(&query), hasher.hash_one(&retried));
     }
 }
@@ -0,0 +1,340 @@
+use std::{cell::RefCell, pin::pin, rc::Rc};
+
+use dioxus::prelude::*;
+use dioxus_core::{provide_root_context, NoOpMutations, VirtualDom};
+use futures_util::{future::select, FutureExt};
+
+use crate::{
+    client::QueryClient,
+    clock::{MockClock, QueryClock},
+    mutation::MutationCapability,
+    query::{QueriesStorage, QueryCapability, QueryInterceptor, QueryStateData},
+};
+
+/// Mount a component under a headless [VirtualDom] to test the queries it uses, without a renderer.
+///
+/// ```rust, no_run
+/// # use dioxus::prelude::*;
+/// # use dioxus_query::prelude::*;
+/// # #[derive(Clone, PartialEq, Hash, Eq)]
+/// # struct GetUserName;
+/// # impl QueryCapability for GetUserName {
+/// #     type Ok = String;
+/// #     type Err = ();
+/// #     type Keys = usize;
+/// #     async fn run(&self, user_id: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+/// #         Ok(format!("User {user_id}"))
+/// #     }
+/// # }
+/// fn app() -> Element {
+///     let user_name = use_query(Query::new(0, GetUserName));
+///     rsx!( p { "{user_name.read().state():?}" } )
+/// }
+///
+/// # async fn test() {
+/// let mut tester = QueryTester::new(app);
+/// let probe = tester.probe::<GetUserName>();
+/// tester.settle().await;
+///
+/// assert_eq!(probe.runs(&0), 1);
+/// assert_eq!(tester.settled::<GetUserName>(&0), Some(Ok("User 0".to_string())));
+/// # }
+/// ```
+pub struct QueryTester {
+    dom: VirtualDom,
+    mounted: bool,
+}
+
+impl QueryTester {
+    /// Create a tester for the given component. It's not rendered until [QueryTester::settle] is called,
+    /// so probes and clocks can be set up before its queries run.
+    pub fn new(app: fn() -> Element) -> Self {
+        Self {
+            dom: VirtualDom::new(app),
+            mounted: false,
+        }
+    }
+
+    /// The [VirtualDom] of the component.
+    pub fn dom(&mut self) -> &mut VirtualDom {
+        &mut self.dom
+    }
+
+    /// Run the given function in the root scope of the component, e.g to use the [QueryClient].
+    pub fn in_runtime<T>(&self, f: impl FnOnce() -> T) -> T {
+        self.dom.in_scope(ScopeId::ROOT, f)
+    }
+
+    /// Use a [MockClock] in the component, so time only moves forward when it's advanced.
+    pub fn mock_clock(&self) -> MockClock {
+        let clock = MockClock::new();
+        self.in_runtime(|| QueryClock::provide(clock.clone()));
+        clock
+    }
+
+    /// Get the [QueryProbe] of the given query type, to count and fail its executions.
+    pub fn probe<Q: QueryCapability>(&self) -> QueryProbe<Q> {
+        self.in_runtime(QueryProbe::current)
+    }
+
+    /// Get the [MutationProbe] of the given mutation type, to count and fail its executions.
+    pub fn mutation_probe<Q: MutationCapability>(&self) -> MutationProbe<Q> {
+        self.in_runtime(MutationProbe::current)
+    }
+
+    /// Serve the queries of the given type with the given function instead of running them,
+    /// see [QueriesStorage::mock].
+    pub fn mock<Q: QueryCapability>(
+        &self,
+        run: impl Fn(&Q::Keys) -> Result<Q::Ok, Q::Err> + 'static,
+    ) {
+        self.in_runtime(|| QueriesStorage::<Q>::mock(run))
+    }
+
+    /// Render the component and process its work until no query or mutation is running,
+    /// other than the queries waiting for the [MockClock] to advance or to be back online.
+    pub async fn settle(&mut self) {
+        if !self.mounted {
+            self.dom.rebuild_in_place();
+            self.mounted = true;
+        }
+        loop {
+            // Process all the work that is ready
+            while self.dom.wait_for_work().now_or_never().is_some() {
+                self.dom.render_immediate(&mut NoOpMutations);
+            }
+
+            if !self.in_runtime(|| QueryClient::current().is_busy()) {
+                break;
+            }
+
+            // Wait for the running queries and mutations, not all of them lead to renders,
+            // e.g those without subscribers
+            let client = self.in_runtime(QueryClient::current);
+            select(pin!(self.dom.wait_for_work()), pin!(client.busy_changed())).await;
+            self.dom.render_immediate(&mut NoOpMutations);
+        }
+    }
+
+    /// Run the given function with the state of a query with the given keys, if it's cached.
+    pub fn query_state<Q: QueryCapability, T>(
+        &self,
+        keys: &Q::Keys,
+        f: impl FnOnce(&QueryStateData<Q>) -> T,
+    ) -> Option<T> {
+        self.in_runtime(|| QueriesStorage::<Q>::current().with_state(keys, f))
+    }
+
+    /// Get the result of a query with the given keys, if it's cached and settled.
+    pub fn settled<Q: QueryCapability>(&self, keys: &Q::Keys) -> Option<Result<Q::Ok, Q::Err>>
+    where
+        Q::Ok: Clone,
+        Q::Err: Clone,
+    {
+        self.query_state::<Q, _>(keys, |state| match state {
+            QueryStateData::Settled { res, .. } => Some(res.clone()),
+            _ => None,
+        })
+        .flatten()
+    }
+}
+
+struct InjectedFailure<K, E> {
+    keys: K,
+    err: Rc<dyn Fn() -> E>,
+    /// How many more executions to fail, if limited.
+    remaining: Option<usize>,
+}
+
+/// The executions and injected failures of a [QueryProbe] or a [MutationProbe].
+struct ProbeState<K, E> {
+    runs: Vec<(K, usize)>,
+    failures: Vec<InjectedFailure<K, E>>,
+}
+
+impl<K: PartialEq + Clone, E> ProbeState<K, E> {
+    fn new() -> Self {
+        Self {
+            runs: Vec::new(),
+            failures: Vec::new(),
+        }
+    }
+
+    fn runs(&self, keys: &K) -> usize {
+        self.runs
+            .iter()
+            .find(|(run_keys, _)| run_keys == keys)
+            .map_or(0, |(_, runs)| *runs)
+    }
+
+    fn total_runs(&self) -> usize {
+        self.runs.iter().map(|(_, runs)| runs).sum()
+    }
+
+    fn count_run(&mut self, keys: &K) {
+        match self.runs.iter_mut().find(|(run_keys, _)| run_keys == keys) {
+            Some((_, runs)) => *runs += 1,
+            None => self.runs.push((keys.clone(), 1)),
+        }
+    }
+
+    fn inject(&mut self, keys: K, err: Rc<dyn Fn() -> E>, remaining: Option<usize>) {
+        self.failures.retain(|failure| failure.keys != keys);
+        self.failures.push(InjectedFailure {
+            keys,
+            err,
+            remaining,
+        });
+    }
+
+    fn heal(&mut self, keys: &K) {
+        self.failures.retain(|failure| failure.keys != *keys);
+    }
+
+    /// Get the injected failure for the given keys, if any.
+    fn failure(&mut self, keys: &K) -> Option<E> {
+        let i = self
+            .failures
+            .iter()
+            .position(|failure| failure.keys == *keys)?;
+        let failure = &mut self.failures[i];
+        let err = (failure.err)();
+        if let Some(remaining) = &mut failure.remaining {
+            *remaining -= 1;
+            if *remaining == 0 {
+                self.failures.remove(i);
+            }
+        }
+        Some(err)
+    }
+}
+
+/// Count the executions of a query type and make them fail on demand, see [QueryTester::probe].
+///
+/// Every execution is counted, including retries and failed ones.
+pub struct QueryProbe<Q: QueryCapability> {
+    state: Rc<RefCell<ProbeState<Q::Keys, Q::Err>>>,
+}
+
+impl<Q: QueryCapability> Clone for QueryProbe<Q> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<Q: QueryCapability> QueryProbe<Q> {
+    /// Get the [QueryProbe] of this query type, creating it if necessary.
+    pub fn current() -> Self {
+        match try_consume_context::<QueryProbe<Q>>() {
+            Some(probe) => probe,
+            None => {
+                let probe = QueryProbe {
+                    state: Rc::new(RefCell::new(ProbeState::new())),
+                };
+                QueriesStorage::<Q>::current().intercept(probe.clone());
+                provide_root_context(probe)
+            }
+        }
+    }
+
+    /// How many times have the queries with the given keys run.
+    pub fn runs(&self, keys: &Q::Keys) -> usize {
+        self.state.borrow().runs(keys)
+    }
+
+    /// How many times have the queries of this type run.
+    pub fn total_runs(&self) -> usize {
+        self.state.borrow().total_runs()
+    }
+
+    /// Make every execution of the queries with the given keys fail with the given error,
+    /// until [QueryProbe::heal] is called.
+    pub fn fail(&self, keys: Q::Keys, err: impl Fn() -> Q::Err + 'static) {
+        self.state.borrow_mut().inject(keys, Rc::new(err), None);
+    }
+
+    /// Make the next execution of the queries with the given keys fail with the given error.
+    pub fn fail_once(&self, keys: Q::Keys, err: impl Fn() -> Q::Err + 'static) {
+        self.state.borrow_mut().inject(keys, Rc::new(err), Some(1));
+    }
+
+    /// Stop failing the executions of the queries with the given keys.
+    pub fn heal(&self, keys: &Q::Keys) {
+        self.state.borrow_mut().heal(keys);
+    }
+}
+
+impl<Q: QueryCapability> QueryInterceptor<Q> for QueryProbe<Q> {
+    fn intercept(&self, keys: &Q::Keys) -> Option<Result<Q::Ok, Q::Err>> {
+        self.state.borrow_mut().failure(keys).map(Err)
+    }
+
+    fn ran(&self, keys: &Q::Keys, _res: &Result<Q::Ok, Q::Err>) {
+        self.state.borrow_mut().count_run(keys);
+    }
+}
+
+/// Count the executions of a mutation type and make them fail on demand, see [QueryTester::mutation_probe].
+///
+/// Every execution is counted, including failed ones.
+pub struct MutationProbe<Q: MutationCapability> {
+    state: Rc<RefCell<ProbeState<Q::Keys, Q::Err>>>,
+}
+
+impl<Q: MutationCapability> Clone for MutationProbe<Q> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<Q: MutationCapability> MutationProbe<Q> {
+    /// Get the [MutationProbe] of this mutation type, creating it if necessary.
+    pub fn current() -> Self {
+        match try_consume_context::<MutationProbe<Q>>() {
+            Some(probe) => probe,
+            None => provide_root_context(MutationProbe {
+                state: Rc::new(RefCell::new(ProbeState::new())),
+            }),
+        }
+    }
+
+    /// How many times have the mutations with the given keys run.
+    pub fn runs(&self, keys: &Q::Keys) -> usize {
+        self.state.borrow().runs(keys)
+    }
+
+    /// How many times have the mutations of this type run.
+    pub fn total_runs(&self) -> usize {
+        self.state.borrow().total_runs()
+    }
+
+    /// Make every execution of the mutations with the given keys fail with the given error,
+    /// until [MutationProbe::heal] is called.
+    pub fn fail(&self, keys: Q::Keys, err: impl Fn() -> Q::Err + 'static) {
+        self.state.borrow_mut().inject(keys, Rc::new(err), None);
+    }
+
+    /// Make the next execution of the mutations with the given keys fail with the given error.
+    pub fn fail_once(&self, keys: Q::Keys, err: impl Fn() -> Q::Err + 'static) {
+        self.state.borrow_mut().inject(keys, Rc::new(err), Some(1));
+    }
+
+    /// Stop failing the executions of the mutations with the given keys.
+    pub fn heal(&self, keys: &Q::Keys) {
+        self.state.borrow_mut().heal(keys);
+    }
+
+    /// Count an execution of the mutations with the given keys, and get its injected failure if any.
+    ///
+    /// Does nothing if the probe of this mutation type was never created.
+    pub(crate) fn intercept(keys: &Q::Keys) -> Option<Q::Err> {
+        let probe = try_consume_context::<MutationProbe<Q>>()?;
+        let mut state = probe.state.borrow_mut();
+        state.count_run(keys);
+        state.failure(keys)
+    }
+}
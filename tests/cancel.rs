@@ -0,0 +1,71 @@
+mod common;
+
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use common::{mount, Slow};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+#[tokio::test(start_paused = true)]
+async fn cancelling_the_first_load_goes_back_to_pending() {
+    let slow = Slow::new();
+    let (mut harness, query) = mount(Query::new(0, slow.clone()));
+    harness.run_for(Duration::from_millis(100)).await;
+
+    harness.in_app(|| query.get().cancel());
+    assert!(harness.in_app(|| query.get().peek().state().is_pending()));
+
+    // The cancelled execution never settles
+    harness.run_for(Duration::from_secs(5)).await;
+    assert!(harness.in_app(|| query.get().peek().state().is_pending()));
+    assert_eq!(slow.calls(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelling_a_refetch_restores_the_previous_value() {
+    let slow = Slow::new();
+    let (mut harness, query) = mount(Query::new(0, slow.clone()));
+    harness.run_for(Duration::from_secs(5)).await;
+
+    harness.in_app(|| query.get().invalidate());
+    harness.run_for(Duration::from_millis(100)).await;
+    assert!(harness.in_app(|| query.get().peek().state().is_loading()));
+
+    harness.in_app(|| query.get().cancel());
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(slow.calls(), 2);
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(1))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelling_resolves_those_waiting() {
+    let (mut harness, query) = mount(Query::new(0, Slow::new()));
+    harness.run_for(Duration::from_millis(100)).await;
+
+    let resolved = Rc::new(Cell::new(false));
+    harness.in_app(|| {
+        let query = query.get();
+        let resolved = resolved.clone();
+        spawn(async move {
+            query.invalidate_async().await;
+            resolved.set(true);
+        })
+    });
+    harness.run_for(Duration::from_millis(100)).await;
+    assert!(!resolved.get());
+
+    harness.in_app(|| query.get().cancel());
+    harness.run_for(Duration::from_millis(100)).await;
+    assert!(resolved.get());
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancel_matching_cancels_the_running_queries() {
+    let slow = Slow::new();
+    let (mut harness, query) = mount(Query::new(0, slow.clone()));
+    harness.run_for(Duration::from_millis(100)).await;
+
+    harness.in_app(|| QueriesStorage::<Slow>::cancel_matching(0));
+    harness.run_for(Duration::from_secs(5)).await;
+    assert!(harness.in_app(|| query.get().peek().state().is_pending()));
+}
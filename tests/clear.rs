@@ -0,0 +1,48 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{Harness, Slot, Slow};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+#[tokio::test(start_paused = true)]
+async fn clear_only_removes_the_queries_not_in_use() {
+    let slow = Slow::new();
+    let query = Slot::default();
+    let mut harness = Harness::new({
+        let query = query.clone();
+        let slow = slow.clone();
+        move || {
+            let value = use_query(Query::new(1, slow.clone()).stale_time(Duration::MAX));
+            value.read();
+            query.set(value);
+            rsx!()
+        }
+    });
+    harness.run_for(Duration::from_secs(2)).await;
+
+    // Cache another query without subscribing to it
+    let get = {
+        let slow = slow.clone();
+        move || {
+            GetQuery::new(2, slow.clone())
+                .stale_time(Duration::MAX)
+                .clean_time(Duration::MAX)
+        }
+    };
+    harness.run(QueriesStorage::get(get())).await;
+    assert_eq!(slow.calls(), 2);
+
+    harness.in_app(|| QueryClient::current().clear());
+
+    // The query in use keeps its value without running again
+    harness.run_for(Duration::from_secs(2)).await;
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(1))));
+    assert_eq!(slow.calls(), 2);
+
+    // The other one is gone
+    let reader = harness.run(QueriesStorage::get(get())).await;
+    assert!(matches!(*reader.as_settled(), Ok(3)));
+    assert_eq!(slow.calls(), 3);
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{Harness, Slot, Slow};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+/// Subscribe to the given query, using the given clock.
+fn mount(clock: MockClock, query: Query<Slow>) -> Harness {
+    Harness::new(move || {
+        use_hook(|| QueryClock::provide(clock.clone()));
+        use_query(query.clone()).read();
+        rsx!()
+    })
+}
+
+/// Let the queries run, which takes no time of the [MockClock].
+async fn settle(harness: &mut Harness) {
+    harness.run_for(Duration::from_secs(5)).await;
+}
+
+fn get(slow: &Slow, keys: u32) -> GetQuery<Slow> {
+    GetQuery::new(keys, slow.clone())
+        .stale_time(Duration::from_secs(10))
+        .clean_time(Duration::from_secs(30))
+}
+
+#[tokio::test(start_paused = true)]
+async fn queries_go_stale_as_the_clock_advances() {
+    let clock = MockClock::new();
+    let slow = Slow::new();
+    let mut harness = mount(clock.clone(), Query::new(1, slow.clone()));
+    settle(&mut harness).await;
+
+    harness.run(QueriesStorage::get(get(&slow, 2))).await;
+    assert_eq!(slow.calls(), 2);
+
+    clock.advance(Duration::from_secs(9));
+    harness.run(QueriesStorage::get(get(&slow, 2))).await;
+    assert_eq!(slow.calls(), 2);
+
+    clock.advance(Duration::from_secs(1));
+    harness.run(QueriesStorage::get(get(&slow, 2))).await;
+    assert_eq!(slow.calls(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn unused_queries_are_cleaned_as_the_clock_advances() {
+    let clock = MockClock::new();
+    let slow = Slow::new();
+    let mut harness = mount(clock.clone(), Query::new(1, slow.clone()));
+    settle(&mut harness).await;
+
+    harness
+        .run(QueriesStorage::get(get(&slow, 2).stale_time(Duration::MAX)))
+        .await;
+
+    clock.advance(Duration::from_secs(29));
+    settle(&mut harness).await;
+    harness
+        .run(QueriesStorage::get(get(&slow, 2).stale_time(Duration::MAX)))
+        .await;
+    assert_eq!(slow.calls(), 2);
+
+    // Cleaned, so it runs again from scratch
+    clock.advance(Duration::from_secs(31));
+    settle(&mut harness).await;
+    harness
+        .run(QueriesStorage::get(get(&slow, 2).stale_time(Duration::MAX)))
+        .await;
+    assert_eq!(slow.calls(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn intervals_tick_as_the_clock_advances() {
+    let clock = MockClock::new();
+    let slow = Slow::new();
+    let mut harness = mount(
+        clock.clone(),
+        Query::new(1, slow.clone()).interval_time(Duration::from_secs(60)),
+    );
+    settle(&mut harness).await;
+    assert_eq!(slow.calls(), 1);
+
+    clock.advance(Duration::from_secs(59));
+    settle(&mut harness).await;
+    assert_eq!(slow.calls(), 1);
+
+    clock.advance(Duration::from_secs(1));
+    settle(&mut harness).await;
+    assert_eq!(slow.calls(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_state_is_stale_at_the_instant_of_the_clock() {
+    let clock = MockClock::new();
+    let query = Query::new(1, Slow::new()).stale_time(Duration::from_secs(10));
+    let slot = Slot::default();
+    let mut harness = Harness::new({
+        let clock = clock.clone();
+        let query = query.clone();
+        let slot = slot.clone();
+        move || {
+            use_hook(|| QueryClock::provide(clock.clone()));
+            slot.set(use_query(query.clone()));
+            rsx!()
+        }
+    });
+    settle(&mut harness).await;
+
+    let is_stale =
+        |harness: &Harness| harness.in_app(|| slot.get().peek().state().is_stale(&query));
+    assert!(!is_stale(&harness));
+    clock.advance(Duration::from_secs(10));
+    assert!(is_stale(&harness));
+}
@@ -0,0 +1,153 @@
+//! Headless harness to render the components of the tests without a renderer.
+#![allow(dead_code)]
+
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    rc::Rc,
+    time::Duration,
+};
+
+use dioxus::prelude::*;
+use dioxus_core::{ComponentFunction, NoOpMutations, VirtualDom};
+use dioxus_query::prelude::*;
+
+pub struct Harness {
+    dom: VirtualDom,
+}
+
+impl Harness {
+    /// Render the given component.
+    pub fn new<M: 'static>(app: impl ComponentFunction<(), M>) -> Self {
+        let mut dom = VirtualDom::new_with_props(app, ());
+        dom.rebuild_in_place();
+        Self { dom }
+    }
+
+    /// Process the work of the component for the given time.
+    ///
+    /// Meant for tests with a paused tokio clock, which advances on its own while idle.
+    pub async fn run_for(&mut self, duration: Duration) {
+        let _ = tokio::time::timeout(duration, async {
+            loop {
+                self.dom.wait_for_work().await;
+                self.dom.render_immediate(&mut NoOpMutations);
+            }
+        })
+        .await;
+    }
+
+    /// Spawn the given future in the scope of the component and process its work until it finishes.
+    pub async fn run<T: 'static>(&mut self, future: impl Future<Output = T> + 'static) -> T {
+        let output = Rc::new(RefCell::new(None));
+        self.in_app(|| {
+            let output = output.clone();
+            spawn(async move {
+                *output.borrow_mut() = Some(future.await);
+            })
+        });
+        loop {
+            if let Some(output) = output.borrow_mut().take() {
+                return output;
+            }
+            self.run_for(Duration::from_millis(1)).await;
+        }
+    }
+
+    /// Run the given function in the scope of the component.
+    pub fn in_app<T>(&self, f: impl FnOnce() -> T) -> T {
+        self.dom.in_scope(ScopeId::APP, f)
+    }
+
+    /// Re-render the component.
+    pub fn rerender(&mut self) {
+        self.dom.mark_dirty(ScopeId::APP);
+        self.dom.render_immediate(&mut NoOpMutations);
+    }
+}
+
+/// A value shared between a test and its component, e.g to get a hook out of it.
+pub struct Slot<T>(Rc<RefCell<Option<T>>>);
+
+impl<T> Clone for Slot<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Default for Slot<T> {
+    fn default() -> Self {
+        Self(Rc::default())
+    }
+}
+
+impl<T: Clone> Slot<T> {
+    pub fn set(&self, value: T) {
+        *self.0.borrow_mut() = Some(value);
+    }
+
+    pub fn get(&self) -> T {
+        self.0.borrow().clone().expect("Slot is empty.")
+    }
+}
+
+/// Takes a second to resolve with the number of times it ran.
+#[derive(Clone, PartialEq, Hash, Eq)]
+pub struct Slow(Captured<Rc<Cell<u32>>>);
+
+impl Slow {
+    pub fn new() -> Self {
+        Self(Captured(Rc::default()))
+    }
+
+    pub fn calls(&self) -> u32 {
+        self.0.get()
+    }
+}
+
+impl QueryCapability for Slow {
+    type Ok = u32;
+    type Err = ();
+    type Keys = u32;
+
+    async fn run(&self, _keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        let call = self.0.get() + 1;
+        self.0.set(call);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        Ok(call)
+    }
+}
+
+/// Fails as many times as its keys say, then succeeds with the number of attempts.
+#[derive(Clone, PartialEq, Hash, Eq)]
+pub struct Flaky(pub Captured<Rc<Cell<u32>>>);
+
+impl QueryCapability for Flaky {
+    type Ok = u32;
+    type Err = u32;
+    type Keys = u32;
+
+    async fn run(&self, failures: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        let attempt = self.0.get();
+        self.0.set(attempt + 1);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        if attempt < *failures {
+            Err(attempt)
+        } else {
+            Ok(attempt)
+        }
+    }
+}
+
+/// Mount a component that uses the given query, and get it out of it.
+pub fn mount<Q: QueryCapability>(query: Query<Q>) -> (Harness, Slot<UseQuery<Q>>) {
+    let slot = Slot::default();
+    let harness = Harness::new({
+        let slot = slot.clone();
+        move || {
+            slot.set(use_query(query.clone()));
+            rsx!()
+        }
+    });
+    (harness, slot)
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use common::mount;
+use dioxus_query::prelude::*;
+
+#[derive(Clone, PartialEq, Debug)]
+struct Execution {
+    attempt: u32,
+    trigger: QueryTrigger,
+    previous: Option<u32>,
+}
+
+/// Keeps what it saw of every execution, failing the first attempt of each one if its keys say so.
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct Inspect {
+    executions: Captured<Rc<RefCell<Vec<Execution>>>>,
+    cancel_signals: Captured<Rc<RefCell<Vec<CancelSignal>>>>,
+}
+
+impl Inspect {
+    fn new() -> Self {
+        Self {
+            executions: Captured(Rc::default()),
+            cancel_signals: Captured(Rc::default()),
+        }
+    }
+
+    fn executions(&self) -> Vec<Execution> {
+        self.executions.borrow().clone()
+    }
+}
+
+impl QueryCapability for Inspect {
+    type Ok = u32;
+    type Err = ();
+    type Keys = bool;
+
+    async fn run(&self, _keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        unreachable!()
+    }
+
+    async fn run_with_context(
+        &self,
+        fail_first_attempt: &Self::Keys,
+        ctx: &QueryContext<Self>,
+    ) -> Result<Self::Ok, Self::Err> {
+        let previous = ctx.previous().and_then(Result::ok);
+        self.executions.borrow_mut().push(Execution {
+            attempt: ctx.attempt(),
+            trigger: ctx.trigger(),
+            previous,
+        });
+        self.cancel_signals
+            .borrow_mut()
+            .push(ctx.cancel_signal().clone());
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        if *fail_first_attempt && ctx.attempt() == 0 {
+            Err(())
+        } else {
+            Ok(previous.unwrap_or_default() + 1)
+        }
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn executions_know_their_trigger_and_previous_value() {
+    let inspect = Inspect::new();
+    let (mut harness, query) = mount(Query::new(false, inspect.clone()));
+    harness.run_for(Duration::from_secs(5)).await;
+
+    harness.in_app(|| query.get().invalidate());
+    harness.run_for(Duration::from_secs(5)).await;
+
+    assert_eq!(
+        inspect.executions(),
+        [
+            Execution {
+                attempt: 0,
+                trigger: QueryTrigger::Mount,
+                previous: None,
+            },
+            Execution {
+                attempt: 0,
+                trigger: QueryTrigger::Invalidate,
+                previous: Some(1),
+            },
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn retries_know_their_attempt() {
+    let inspect = Inspect::new();
+    let query = Query::new(true, inspect.clone()).retry(QueryRetry::new(1));
+    let (mut harness, query) = mount(query);
+    harness.run_for(Duration::from_secs(10)).await;
+
+    let attempts = inspect
+        .executions()
+        .iter()
+        .map(|execution| execution.attempt)
+        .collect::<Vec<_>>();
+    assert_eq!(attempts, [0, 1]);
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(1))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelling_signals_the_execution() {
+    let inspect = Inspect::new();
+    let (mut harness, query) = mount(Query::new(false, inspect.clone()));
+    harness.run_for(Duration::from_millis(100)).await;
+
+    let signal = inspect.cancel_signals.borrow()[0].clone();
+    assert!(!signal.is_cancelled());
+
+    harness.in_app(|| query.get().cancel());
+    assert!(signal.is_cancelled());
+    signal.cancelled().await;
+}
+
+/// Keeps the previous value while it runs, and settles with it.
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct Keep;
+
+impl QueryCapability for Keep {
+    type Ok = u32;
+    type Err = ();
+    type Keys = ();
+
+    async fn run(&self, _keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        unreachable!()
+    }
+
+    async fn run_with_context(
+        &self,
+        _keys: &Self::Keys,
+        ctx: &QueryContext<Self>,
+    ) -> Result<Self::Ok, Self::Err> {
+        let previous = ctx.previous();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        previous.unwrap_or(Ok(0))
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_previous_value_can_be_kept_while_the_state_changes() {
+    let (mut harness, query) = mount(Query::new((), Keep));
+    harness.run_for(Duration::from_secs(5)).await;
+
+    harness.in_app(|| query.get().invalidate());
+    harness.run_for(Duration::from_millis(100)).await;
+    harness.in_app(|| QueriesStorage::<Keep>::set_data((), 5));
+    harness.run_for(Duration::from_secs(5)).await;
+
+    // The write discarded the running execution
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(5))));
+}
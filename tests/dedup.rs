@@ -0,0 +1,72 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{mount, Harness, Slow};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+#[tokio::test(start_paused = true)]
+async fn subscribers_share_the_running_execution() {
+    let slow = Slow::new();
+    let mut harness = Harness::new({
+        let slow = slow.clone();
+        move || {
+            use_query(Query::new(0, slow.clone()));
+            use_query(Query::new(0, slow.clone()));
+            rsx!()
+        }
+    });
+
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(slow.calls(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn invalidations_join_the_running_execution() {
+    let slow = Slow::new();
+    let (mut harness, query) = mount(Query::new(0, slow.clone()).refetch_mode(RefetchMode::Join));
+    harness.run_for(Duration::from_millis(100)).await;
+
+    harness.in_app(|| query.get().invalidate());
+    harness.in_app(|| query.get().invalidate());
+    harness.run_for(Duration::from_secs(5)).await;
+
+    assert_eq!(slow.calls(), 1);
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(1))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn queued_invalidations_run_once_after_the_running_execution() {
+    let slow = Slow::new();
+    let (mut harness, query) = mount(Query::new(0, slow.clone()).refetch_mode(RefetchMode::Queue));
+    harness.run_for(Duration::from_millis(100)).await;
+
+    harness.in_app(|| query.get().invalidate());
+    harness.in_app(|| query.get().invalidate());
+    harness.run_for(Duration::from_millis(500)).await;
+    assert_eq!(slow.calls(), 1);
+
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(slow.calls(), 2);
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(2))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelling_invalidations_restart_the_running_execution() {
+    let slow = Slow::new();
+    let (mut harness, query) = mount(Query::new(0, slow.clone()).refetch_mode(RefetchMode::Cancel));
+    harness.run_for(Duration::from_millis(500)).await;
+
+    harness.in_app(|| query.get().invalidate());
+    harness.run_for(Duration::from_millis(100)).await;
+    assert_eq!(slow.calls(), 2);
+
+    // The cancelled execution would have settled by now
+    harness.run_for(Duration::from_millis(600)).await;
+    assert!(harness.in_app(|| query.get().peek().state().is_loading()));
+
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(slow.calls(), 2);
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(2))));
+}
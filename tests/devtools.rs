@@ -0,0 +1,77 @@
+#![cfg(feature = "devtools")]
+
+mod common;
+
+use std::time::Duration;
+
+use common::{Harness, Slow};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+/// Subscribe to the query with the keys `1`, and cache the one with the keys `2` without subscribing to it.
+async fn mount(slow: &Slow) -> Harness {
+    let mut harness = Harness::new({
+        let slow = slow.clone();
+        move || {
+            use_query(Query::new(1, slow.clone()).stale_time(Duration::MAX)).read();
+            rsx!()
+        }
+    });
+    let get = GetQuery::new(2, slow.clone())
+        .stale_time(Duration::MAX)
+        .clean_time(Duration::MAX);
+    harness.run(QueriesStorage::get(get)).await;
+    harness.run_for(Duration::from_secs(5)).await;
+    harness
+}
+
+fn entries(harness: &Harness) -> Vec<QueryEntry> {
+    harness.in_app(|| QueryClient::current().entries())
+}
+
+#[tokio::test(start_paused = true)]
+async fn lists_the_cached_queries() {
+    let slow = Slow::new();
+    let harness = mount(&slow).await;
+
+    let entries = entries(&harness);
+    assert_eq!(entries.len(), 2);
+    assert!(entries
+        .iter()
+        .all(|entry| entry.type_name == std::any::type_name::<Slow>()));
+    assert!(entries.iter().all(|entry| entry.state == "Settled (Ok)"));
+
+    harness.in_app(|| QueryClient::current().devtools_debug::<Slow>());
+    let entries = self::entries(&harness);
+    assert_eq!(entries[0].keys, "1");
+    assert_eq!(entries[0].subscribers, 1);
+    assert!(entries[0].state.contains("Ok(1)"));
+    assert_eq!(entries[1].keys, "2");
+    assert_eq!(entries[1].subscribers, 0);
+    assert!(entries[1].state.contains("Ok(2)"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn invalidates_and_removes_entries() {
+    let slow = Slow::new();
+    let mut harness = mount(&slow).await;
+    harness.in_app(|| QueryClient::current().devtools_debug::<Slow>());
+
+    let entries = entries(&harness);
+    let entry = entries[0].clone();
+    harness
+        .run(async move { QueryClient::current().invalidate_entry(&entry).await })
+        .await;
+    assert_eq!(slow.calls(), 3);
+    assert!(self::entries(&harness)[0].state.contains("Ok(3)"));
+
+    // Only the entry without subscribers is removed
+    harness.in_app(|| {
+        for entry in &entries {
+            QueryClient::current().remove_entry(entry);
+        }
+    });
+    let entries = self::entries(&harness);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].keys, "1");
+}
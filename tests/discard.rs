@@ -0,0 +1,96 @@
+mod common;
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+use common::{Harness, Slot};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+/// Takes a second to resolve with the number of times it ran, and keeps the discarded results.
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct Slow {
+    calls: Captured<Rc<Cell<u32>>>,
+    discarded: Captured<Discarded>,
+}
+
+impl QueryCapability for Slow {
+    type Ok = u32;
+    type Err = ();
+    type Keys = ();
+
+    async fn run(&self, _keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        let call = self.calls.get() + 1;
+        self.calls.set(call);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        Ok(call)
+    }
+
+    fn on_discarded(&self, _keys: &Self::Keys, result: &Result<Self::Ok, Self::Err>) {
+        self.discarded.borrow_mut().push(*result.as_ref().unwrap());
+    }
+}
+
+type Discarded = Rc<RefCell<Vec<u32>>>;
+
+fn mount() -> (Harness, Slot<UseQuery<Slow>>, Discarded) {
+    let discarded = Rc::new(RefCell::new(Vec::new()));
+    let slot = Slot::default();
+    let harness = Harness::new({
+        let discarded = discarded.clone();
+        let slot = slot.clone();
+        move || {
+            let query = use_query(Query::new(
+                (),
+                Slow {
+                    calls: Captured(Rc::default()),
+                    discarded: Captured(discarded.clone()),
+                },
+            ));
+            slot.set(query);
+            rsx!()
+        }
+    });
+    (harness, slot, discarded)
+}
+
+#[tokio::test(start_paused = true)]
+async fn writes_discard_the_running_execution() {
+    let (mut harness, slot, discarded) = mount();
+    harness.run_for(Duration::from_millis(100)).await;
+
+    // Wait for the running execution while it gets discarded
+    let resolved = Rc::new(Cell::new(false));
+    harness.in_app(|| {
+        let query = slot.get();
+        let resolved = resolved.clone();
+        spawn(async move {
+            query.invalidate_async().await;
+            resolved.set(true);
+        })
+    });
+    harness.in_app(|| QueriesStorage::<Slow>::set_data((), 42));
+    harness.run_for(Duration::from_secs(5)).await;
+
+    assert_eq!(*discarded.borrow(), [1]);
+    assert!(resolved.get());
+    assert!(harness.in_app(|| matches!(*slot.get().peek().as_settled(), Ok(42))));
+    assert!(!harness.in_app(|| slot.get().peek().state().is_loading()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn executions_after_a_write_settle() {
+    let (mut harness, slot, discarded) = mount();
+    harness.run_for(Duration::from_millis(100)).await;
+    harness.in_app(|| QueriesStorage::<Slow>::set_data((), 42));
+    harness.run_for(Duration::from_secs(5)).await;
+
+    harness.in_app(|| slot.get().invalidate());
+    harness.run_for(Duration::from_secs(5)).await;
+
+    assert_eq!(*discarded.borrow(), [1]);
+    assert!(harness.in_app(|| matches!(*slot.get().peek().as_settled(), Ok(2))));
+}
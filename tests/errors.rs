@@ -0,0 +1,149 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{mount, Harness, Slot, Slow};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+/// Pages from `0` to `2`, starting at `0`.
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct Pages;
+
+impl InfiniteQueryCapability for Pages {
+    type Page = usize;
+    type Err = ();
+    type Keys = ();
+    type PageParam = usize;
+
+    fn initial_page_param(&self, _keys: &Self::Keys) -> Self::PageParam {
+        0
+    }
+
+    async fn run_page(
+        &self,
+        _keys: &Self::Keys,
+        page_param: &Self::PageParam,
+    ) -> Result<Self::Page, Self::Err> {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        Ok(*page_param)
+    }
+
+    fn next_page_param(
+        &self,
+        _last_page: &Self::Page,
+        last_page_param: &Self::PageParam,
+    ) -> Option<Self::PageParam> {
+        (*last_page_param < 2).then_some(last_page_param + 1)
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn fails_without_the_storage() {
+    let (mut harness, query) = mount(Query::new(0, Slow::new()));
+    harness.run_for(Duration::from_secs(2)).await;
+
+    // Outside of the app there is no storage to look the query up in
+    let query = query.get();
+    assert!(matches!(query.try_read(), Err(QueryError::MissingContext)));
+    assert!(matches!(query.try_peek(), Err(QueryError::MissingContext)));
+    assert!(matches!(
+        query.try_suspend(),
+        Err(QueryError::MissingContext)
+    ));
+    assert_eq!(query.try_invalidate(), Err(QueryError::MissingContext));
+    assert!(matches!(
+        query.try_invalidate_async().await,
+        Err(QueryError::MissingContext)
+    ));
+    assert_eq!(query.try_cancel(), Err(QueryError::MissingContext));
+
+    // Inside of it they work
+    harness.in_app(|| {
+        assert!(matches!(*query.try_peek().unwrap().as_settled(), Ok(1)));
+        assert_eq!(query.try_cancel(), Ok(()));
+    });
+}
+
+#[tokio::test(start_paused = true)]
+async fn fails_while_not_settled() {
+    let (mut harness, query) = mount(Query::new(0, Slow::new()));
+    harness.run_for(Duration::from_millis(100)).await;
+
+    harness.in_app(|| {
+        let reader = query.get().try_peek().unwrap();
+        assert!(matches!(
+            reader.try_as_settled(),
+            Err(QueryError::NotSettled)
+        ));
+    });
+
+    harness.run_for(Duration::from_secs(2)).await;
+    harness.in_app(|| {
+        let reader = query.get().try_peek().unwrap();
+        assert!(matches!(reader.try_as_settled().as_deref(), Ok(Ok(1))));
+    });
+}
+
+#[tokio::test(start_paused = true)]
+async fn recreates_an_evicted_entry_instead_of_failing() {
+    let slow = Slow::new();
+    let (mut harness, query) = mount(Query::new(0, slow.clone()));
+    harness.run_for(Duration::from_secs(2)).await;
+
+    // Not subscribed, so clearing evicts it
+    harness.in_app(|| QueryClient::current().clear());
+    assert!(harness.in_app(|| query.get().try_peek().unwrap().state().is_pending()));
+    assert_eq!(harness.in_app(|| query.get().try_cancel()), Ok(()));
+
+    harness.run_for(Duration::from_secs(2)).await;
+    assert_eq!(slow.calls(), 2);
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(2))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn infinite_queries_fail_without_the_storage() {
+    let slot = Slot::default();
+    let mut harness = Harness::new({
+        let slot = slot.clone();
+        move || {
+            slot.set(use_infinite_query(Query::new((), Infinite(Pages))));
+            rsx!()
+        }
+    });
+    harness.run_for(Duration::from_secs(2)).await;
+
+    let query = slot.get();
+    assert!(matches!(query.try_read(), Err(QueryError::MissingContext)));
+    assert!(matches!(query.try_peek(), Err(QueryError::MissingContext)));
+    assert_eq!(query.try_has_next_page(), Err(QueryError::MissingContext));
+    assert_eq!(
+        query.try_has_previous_page(),
+        Err(QueryError::MissingContext)
+    );
+    assert_eq!(query.try_fetch_next_page(), Err(QueryError::MissingContext));
+    assert_eq!(
+        query.try_fetch_previous_page(),
+        Err(QueryError::MissingContext)
+    );
+    assert!(matches!(
+        query.try_fetch_next_page_async().await,
+        Err(QueryError::MissingContext)
+    ));
+    assert!(matches!(
+        query.try_fetch_previous_page_async().await,
+        Err(QueryError::MissingContext)
+    ));
+
+    harness.in_app(|| {
+        assert_eq!(query.try_has_next_page(), Ok(true));
+        assert_eq!(query.try_has_previous_page(), Ok(false));
+        assert_eq!(query.try_fetch_next_page(), Ok(()));
+    });
+    harness.run_for(Duration::from_secs(2)).await;
+    harness.in_app(|| {
+        let reader = query.try_peek().unwrap();
+        let pages = reader.as_settled();
+        assert_eq!(pages.as_ref().unwrap().len(), 2);
+    });
+}
@@ -0,0 +1,167 @@
+#![cfg(feature = "fixtures")]
+
+mod common;
+
+use std::{
+    any::type_name,
+    cell::Cell,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
+
+use common::{Flaky, Harness, Slot};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+use serde_json::json;
+
+/// Long enough for the throttle not to write the file during the test.
+const HOUR: Duration = Duration::from_secs(60 * 60);
+
+/// A fixture file that doesn't exist yet.
+fn fixture_path(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("dioxus-query-{name}-{}.json", std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn read_fixtures(path: &Path) -> FixtureFile {
+    serde_json::from_slice(&fs::read(path).unwrap()).unwrap()
+}
+
+type Mounted = (
+    Harness,
+    Slot<UseQueryFixtures>,
+    Slot<UseQuery<Flaky>>,
+    Rc<Cell<u32>>,
+);
+
+fn mount(path: &Path, mode: FixtureMode, throttle_time: Duration, failures: u32) -> Mounted {
+    let calls = Rc::new(Cell::new(0));
+    let fixtures_slot = Slot::default();
+    let query_slot = Slot::default();
+    let harness = Harness::new({
+        let path = path.to_path_buf();
+        let calls = calls.clone();
+        let fixtures_slot = fixtures_slot.clone();
+        let query_slot = query_slot.clone();
+        move || {
+            fixtures_slot.set(use_query_fixtures(|| {
+                QueryFixtures::new(path.clone(), mode)
+                    .throttle_time(throttle_time)
+                    .query::<Flaky>()
+            }));
+            query_slot.set(use_query(
+                Query::new(failures, Flaky(Captured(calls.clone())))
+                    .retry(QueryRetry::new(3).delay(Duration::from_millis(100))),
+            ));
+            rsx!()
+        }
+    });
+    (harness, fixtures_slot, query_slot, calls)
+}
+
+#[tokio::test(start_paused = true)]
+async fn records_only_the_final_result_of_each_execution() {
+    let path = fixture_path("final");
+    let (mut harness, fixtures, _, calls) = mount(&path, FixtureMode::Record, HOUR, 2);
+
+    harness.run_for(Duration::from_secs(10)).await;
+    assert_eq!(calls.get(), 3);
+    fixtures.get().flush();
+
+    let file = read_fixtures(&path);
+    assert_eq!(
+        file.queries[type_name::<Flaky>()],
+        vec![QueryFixture {
+            keys: json!(2),
+            result: Ok(json!(2)),
+        }]
+    );
+    let _ = fs::remove_file(&path);
+}
+
+#[tokio::test(start_paused = true)]
+async fn writes_the_file_on_flush_or_drop() {
+    let path = fixture_path("write");
+    let (mut harness, fixtures, _, _) = mount(&path, FixtureMode::Record, HOUR, 0);
+
+    harness.run_for(Duration::from_secs(1)).await;
+    assert!(!path.exists());
+
+    fixtures.get().flush();
+    assert_eq!(read_fixtures(&path).queries[type_name::<Flaky>()].len(), 1);
+
+    // Nothing new to save
+    fs::remove_file(&path).unwrap();
+    fixtures.get().flush();
+    assert!(!path.exists());
+
+    harness.in_app(|| spawn(QueriesStorage::<Flaky>::invalidate_all()));
+    harness.run_for(Duration::from_secs(1)).await;
+    assert!(!path.exists());
+
+    drop(fixtures);
+    drop(harness);
+    assert_eq!(read_fixtures(&path).queries[type_name::<Flaky>()].len(), 2);
+    let _ = fs::remove_file(&path);
+}
+
+#[tokio::test(start_paused = true)]
+async fn replays_the_recorded_results() {
+    let path = fixture_path("replay");
+    let file = FixtureFile {
+        queries: [(
+            type_name::<Flaky>().to_string(),
+            vec![
+                QueryFixture {
+                    keys: json!(0),
+                    result: Err(json!(7)),
+                },
+                QueryFixture {
+                    keys: json!(0),
+                    result: Ok(json!(8)),
+                },
+            ],
+        )]
+        .into(),
+    };
+    fs::write(&path, serde_json::to_vec(&file).unwrap()).unwrap();
+
+    let (mut harness, _, query, calls) = mount(&path, FixtureMode::Replay, HOUR, 0);
+
+    harness.run_for(Duration::from_secs(1)).await;
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(8))));
+    assert_eq!(calls.get(), 0);
+
+    // Replaying doesn't touch the file
+    drop(harness);
+    assert_eq!(read_fixtures(&path), file);
+    let _ = fs::remove_file(&path);
+}
+
+#[tokio::test(start_paused = true)]
+async fn writes_the_file_after_the_throttle_time() {
+    let path = fixture_path("throttle");
+    let (mut harness, _, query, _) = mount(&path, FixtureMode::Record, Duration::from_secs(2), 0);
+
+    harness.run_for(Duration::from_secs(1)).await;
+    assert!(!path.exists());
+    harness.run_for(Duration::from_secs(2)).await;
+    assert_eq!(read_fixtures(&path).queries[type_name::<Flaky>()].len(), 1);
+
+    // The results recorded meanwhile are written at once
+    fs::remove_file(&path).unwrap();
+    harness.in_app(|| query.get().invalidate());
+    harness.run_for(Duration::from_millis(500)).await;
+    harness.in_app(|| query.get().invalidate());
+    harness.run_for(Duration::from_secs(1)).await;
+    assert!(!path.exists());
+    harness.run_for(Duration::from_secs(2)).await;
+    assert_eq!(read_fixtures(&path).queries[type_name::<Flaky>()].len(), 3);
+
+    drop(harness);
+    let _ = fs::remove_file(&path);
+}
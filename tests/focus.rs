@@ -0,0 +1,60 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{Harness, Slow};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+/// Subscribe to each of the given queries.
+fn mount(queries: Vec<Query<Slow>>) -> Harness {
+    Harness::new(move || {
+        for query in &queries {
+            use_query(query.clone()).read();
+        }
+        rsx!()
+    })
+}
+
+async fn refocus(harness: &mut Harness) {
+    harness.in_app(|| {
+        let focus_manager = FocusManager::current();
+        focus_manager.set_focused(false);
+        assert!(!focus_manager.is_focused());
+        focus_manager.set_focused(true);
+    });
+    harness.run_for(Duration::from_secs(5)).await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn refetches_stale_queries_when_focused() {
+    let slow = Slow::new();
+    let mut harness = mount(vec![Query::new(1, slow.clone()).refetch_on_focus(true)]);
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(slow.calls(), 1);
+
+    refocus(&mut harness).await;
+    assert_eq!(slow.calls(), 2);
+
+    // Staying focused does nothing
+    harness.in_app(|| FocusManager::current().set_focused(true));
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(slow.calls(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn skips_fresh_queries_and_those_without_refetch_on_focus() {
+    let fresh = Slow::new();
+    let other = Slow::new();
+    let mut harness = mount(vec![
+        Query::new(1, fresh.clone())
+            .refetch_on_focus(true)
+            .stale_time(Duration::MAX),
+        Query::new(2, other.clone()),
+    ]);
+    harness.run_for(Duration::from_secs(5)).await;
+
+    refocus(&mut harness).await;
+    assert_eq!(fresh.calls(), 1);
+    assert_eq!(other.calls(), 1);
+}
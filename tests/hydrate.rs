@@ -0,0 +1,88 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use std::time::Duration;
+
+use common::{mount, Harness, Slot, Slow};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+use serde_json::json;
+
+#[tokio::test(start_paused = true)]
+async fn dehydrates_the_settled_queries() {
+    let (mut harness, _) = mount(Query::new(7, Slow::new()));
+    harness.run_for(Duration::from_secs(5)).await;
+
+    let state = harness.in_app(|| QueryHydration::new().query::<Slow>().dehydrate());
+    let queries = &state.queries[std::any::type_name::<Slow>()];
+    assert_eq!(queries.len(), 1);
+    assert_eq!(queries[0].keys, json!(7));
+    assert_eq!(queries[0].result, Ok(json!(1)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn hydrated_queries_skip_the_mount_fetch() {
+    let (mut harness, _) = mount(Query::new(7, Slow::new()));
+    harness.run_for(Duration::from_secs(5)).await;
+    let state = harness.in_app(|| QueryHydration::new().query::<Slow>().dehydrate());
+    drop(harness);
+
+    let slow = Slow::new();
+    let query = Slot::default();
+    let mut harness = Harness::new({
+        let slow = slow.clone();
+        let query = query.clone();
+        move || {
+            use_hook(|| QueryHydration::new().query::<Slow>().hydrate(&state));
+            query.set(use_query(Query::new(7, slow.clone())));
+            rsx!()
+        }
+    });
+    harness.run_for(Duration::from_secs(5)).await;
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(1))));
+    assert_eq!(slow.calls(), 0);
+
+    // It's stale, so it runs as usual from now on
+    harness.in_app(|| query.get().invalidate());
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(slow.calls(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn hydrated_queries_mounted_later_are_fetched_if_stale() {
+    let (mut harness, _) = mount(Query::new(7, Slow::new()));
+    harness.run_for(Duration::from_secs(5)).await;
+    let state = harness.in_app(|| QueryHydration::new().query::<Slow>().dehydrate());
+    drop(harness);
+
+    #[component]
+    fn Late(slow: Slow) -> Element {
+        use_query(Query::new(7, slow)).read();
+        rsx!()
+    }
+
+    let slow = Slow::new();
+    let show = Slot::<Signal<bool>>::default();
+    let mut harness = Harness::new({
+        let slow = slow.clone();
+        let show = show.clone();
+        move || {
+            use_hook(|| QueryHydration::new().query::<Slow>().hydrate(&state));
+            let visible = use_signal(|| false);
+            show.set(visible);
+            rsx! {
+                if visible() {
+                    Late { slow: slow.clone() }
+                }
+            }
+        }
+    });
+    harness.run_for(Duration::from_secs(60)).await;
+    assert_eq!(slow.calls(), 0);
+
+    // E.g after a route change, long after the hydration
+    harness.in_app(|| show.get().set(true));
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(slow.calls(), 1);
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use std::{cell::RefCell, collections::HashSet, rc::Rc, time::Duration};
+
+use common::{Harness, Slot};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+/// Pages from `0` to `3` that take a second to load, starting at `1`.
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct Pages {
+    failing: Captured<Rc<RefCell<HashSet<usize>>>>,
+    fetched: Captured<Rc<RefCell<Vec<usize>>>>,
+}
+
+impl Pages {
+    fn new() -> Self {
+        Self {
+            failing: Captured(Rc::default()),
+            fetched: Captured(Rc::default()),
+        }
+    }
+}
+
+impl InfiniteQueryCapability for Pages {
+    type Page = usize;
+    type Err = usize;
+    type Keys = ();
+    type PageParam = usize;
+
+    fn initial_page_param(&self, _keys: &Self::Keys) -> Self::PageParam {
+        1
+    }
+
+    async fn run_page(
+        &self,
+        _keys: &Self::Keys,
+        page_param: &Self::PageParam,
+    ) -> Result<Self::Page, Self::Err> {
+        self.fetched.borrow_mut().push(*page_param);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        if self.failing.borrow().contains(page_param) {
+            Err(*page_param)
+        } else {
+            Ok(*page_param)
+        }
+    }
+
+    fn next_page_param(
+        &self,
+        _last_page: &Self::Page,
+        last_page_param: &Self::PageParam,
+    ) -> Option<Self::PageParam> {
+        (*last_page_param < 3).then_some(last_page_param + 1)
+    }
+
+    fn previous_page_param(
+        &self,
+        _first_page: &Self::Page,
+        first_page_param: &Self::PageParam,
+    ) -> Option<Self::PageParam> {
+        first_page_param.checked_sub(1)
+    }
+}
+
+fn mount(pages: &Pages) -> (Harness, Slot<UseInfiniteQuery<Pages>>) {
+    let slot = Slot::default();
+    let harness = Harness::new({
+        let pages = pages.clone();
+        let slot = slot.clone();
+        move || {
+            let query = use_infinite_query(Query::new((), Infinite(pages.clone())));
+            // Subscribe like a component showing the loading pages would
+            query.is_fetching_next_page();
+            query.is_fetching_previous_page();
+            slot.set(query);
+            rsx!()
+        }
+    });
+    (harness, slot)
+}
+
+fn loaded(harness: &Harness, query: &Slot<UseInfiniteQuery<Pages>>) -> Vec<usize> {
+    harness.in_app(|| {
+        let reader = query.get().peek();
+        let state = reader.state();
+        state.ok().unwrap().pages().copied().collect()
+    })
+}
+
+fn page_error(harness: &Harness, query: &Slot<UseInfiniteQuery<Pages>>) -> Option<usize> {
+    harness.in_app(|| {
+        let reader = query.get().peek();
+        let state = reader.state();
+        state.ok().unwrap().page_error().copied()
+    })
+}
+
+#[tokio::test(start_paused = true)]
+async fn fetches_the_next_and_previous_pages() {
+    let pages = Pages::new();
+    let (mut harness, query) = mount(&pages);
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(loaded(&harness, &query), [1]);
+    assert!(harness.in_app(|| query.get().has_next_page()));
+    assert!(harness.in_app(|| query.get().has_previous_page()));
+
+    harness.in_app(|| query.get().fetch_next_page());
+    harness.run_for(Duration::from_secs(5)).await;
+    harness.in_app(|| query.get().fetch_previous_page());
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(loaded(&harness, &query), [0, 1, 2]);
+    assert!(!harness.in_app(|| query.get().has_previous_page()));
+
+    harness.in_app(|| query.get().fetch_next_page());
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(loaded(&harness, &query), [0, 1, 2, 3]);
+    assert!(!harness.in_app(|| query.get().has_next_page()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn tracks_the_pages_being_fetched() {
+    let pages = Pages::new();
+    let (mut harness, query) = mount(&pages);
+    harness.run_for(Duration::from_secs(5)).await;
+    assert!(!harness.in_app(|| query.get().is_fetching_next_page()));
+
+    harness.in_app(|| query.get().fetch_next_page());
+    harness.run_for(Duration::from_millis(100)).await;
+    assert!(harness.in_app(|| query.get().is_fetching_next_page()));
+    assert!(!harness.in_app(|| query.get().is_fetching_previous_page()));
+
+    harness.run_for(Duration::from_secs(5)).await;
+    assert!(!harness.in_app(|| query.get().is_fetching_next_page()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn page_errors_keep_the_loaded_pages() {
+    let pages = Pages::new();
+    let (mut harness, query) = mount(&pages);
+    harness.run_for(Duration::from_secs(5)).await;
+
+    pages.failing.borrow_mut().insert(2);
+    harness.in_app(|| query.get().fetch_next_page());
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(loaded(&harness, &query), [1]);
+    assert_eq!(page_error(&harness, &query), Some(2));
+
+    pages.failing.borrow_mut().clear();
+    harness.in_app(|| query.get().fetch_next_page());
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(loaded(&harness, &query), [1, 2]);
+    assert_eq!(page_error(&harness, &query), None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn refetches_all_the_loaded_pages() {
+    let pages = Pages::new();
+    let (mut harness, query) = mount(&pages);
+    harness.run_for(Duration::from_secs(5)).await;
+    harness.in_app(|| query.get().fetch_next_page());
+    harness.run_for(Duration::from_secs(5)).await;
+
+    pages.fetched.borrow_mut().clear();
+    harness.in_app(|| query.get().query().invalidate());
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(*pages.fetched.borrow(), [1, 2]);
+    assert_eq!(loaded(&harness, &query), [1, 2]);
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use common::{mount, Harness, Slot, Slow};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+#[tokio::test(start_paused = true)]
+async fn fresh_initial_data_is_not_fetched() {
+    let slow = Slow::new();
+    let query = Query::new(0, slow.clone())
+        .stale_time(Duration::from_secs(60))
+        .initial_data(42, Instant::now());
+    let (mut harness, query) = mount(query);
+    harness.run_for(Duration::from_secs(5)).await;
+
+    assert_eq!(slow.calls(), 0);
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(42))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn stale_initial_data_is_shown_while_fetching() {
+    let slow = Slow::new();
+    let updated_at = Instant::now();
+    tokio::time::advance(Duration::from_secs(120)).await;
+    let query = Query::new(0, slow.clone())
+        .stale_time(Duration::from_secs(60))
+        .initial_data(42, updated_at);
+    let (mut harness, query) = mount(query);
+    harness.run_for(Duration::from_millis(100)).await;
+
+    assert_eq!(slow.calls(), 1);
+    assert!(harness.in_app(|| {
+        let reader = query.get().peek();
+        let state = reader.state();
+        state.is_loading() && state.ok() == Some(&42) && !reader.is_placeholder()
+    }));
+
+    harness.run_for(Duration::from_secs(5)).await;
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(1))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn placeholder_data_is_shown_until_the_first_value() {
+    let query = Query::new(7, Slow::new()).placeholder_data(|keys| keys * 100);
+    let (mut harness, query) = mount(query);
+    harness.run_for(Duration::from_millis(100)).await;
+
+    assert!(harness.in_app(|| {
+        let reader = query.get().peek();
+        let state = reader.state();
+        state.is_loading()
+            && state.ok() == Some(&700)
+            && reader.placeholder() == Some(QueryPlaceholder::PlaceholderData)
+    }));
+
+    // The placeholder is never cached
+    let cached = harness.in_app(|| {
+        let mut cached = None;
+        QueriesStorage::<Slow>::update_data(7, |value| {
+            cached = value.copied();
+            0
+        });
+        cached
+    });
+    assert_eq!(cached, None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn placeholder_data_is_replaced_once_settled() {
+    let query = Query::new(7, Slow::new()).placeholder_data(|keys| keys * 100);
+    let (mut harness, query) = mount(query);
+    harness.run_for(Duration::from_secs(5)).await;
+
+    assert!(harness.in_app(|| {
+        let reader = query.get().peek();
+        let is_settled = matches!(*reader.as_settled(), Ok(1));
+        is_settled && !reader.is_placeholder()
+    }));
+}
+
+#[tokio::test(start_paused = true)]
+async fn rebuilding_the_initial_data_keeps_the_cached_entry() {
+    let slow = Slow::new();
+    let renders = Rc::new(Cell::new(0));
+    let slot = Slot::default();
+    let mut harness = Harness::new({
+        let slow = slow.clone();
+        let renders = renders.clone();
+        let slot = slot.clone();
+        move || {
+            renders.set(renders.get() + 1);
+            let query = Query::new(0, slow.clone())
+                .stale_time(Duration::from_secs(60))
+                .initial_data(renders.get(), QueryClock::current().now());
+            slot.set(use_query(query));
+            rsx!()
+        }
+    });
+
+    for _ in 0..5 {
+        harness.run_for(Duration::from_millis(10)).await;
+        harness.rerender();
+    }
+    assert_eq!(renders.get(), 6);
+    assert_eq!(slow.calls(), 0);
+    assert!(harness.in_app(|| matches!(*slot.get().peek().as_settled(), Ok(1))));
+}
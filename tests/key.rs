@@ -0,0 +1,139 @@
+mod common;
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use common::Harness;
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+#[derive(Clone, PartialEq, Hash, Eq, Debug, QueryKey)]
+struct UserId(u32);
+
+#[derive(Clone, PartialEq, Hash, Eq, Debug, QueryKey)]
+struct PostId(u32);
+
+#[derive(Clone, PartialEq, Hash, Eq, Debug, QueryKey)]
+struct OrgId(u32);
+
+#[derive(Clone, PartialEq, Hash, Eq, Debug, QueryKey)]
+struct Comment {
+    post: PostId,
+    index: (u32, u32),
+}
+
+#[derive(Clone, PartialEq, Hash, Eq, Debug, QueryKey)]
+enum Filter {
+    All,
+    Unread,
+}
+
+#[test]
+fn matches_by_prefix() {
+    let key = (UserId(4), PostId(2), Filter::All);
+    assert!(key.matches_pattern(&()));
+    assert!(key.matches_pattern(&UserId(4)));
+    assert!(key.matches_pattern(&(UserId(4), PostId(2))));
+    assert!(key.matches_pattern(&(UserId(4), PostId(2), Filter::All)));
+    assert!(!key.matches_pattern(&UserId(5)));
+    assert!(!key.matches_pattern(&(UserId(4), PostId(3))));
+    assert!(!key.matches_pattern(&(UserId(4), PostId(2), Filter::All, 1)));
+}
+
+#[test]
+fn matches_any_segment() {
+    let key = (UserId(4), PostId(2), Filter::Unread);
+    assert!(key.matches_pattern(&Any));
+    assert!(key.matches_pattern(&(Any, PostId(2))));
+    assert!(key.matches_pattern(&(UserId(4), Any, Filter::Unread)));
+    assert!(!key.matches_pattern(&(Any, PostId(3))));
+    assert!(!key.matches_pattern(&(Any, Any, Filter::All)));
+}
+
+#[test]
+fn segments_of_different_types_never_match() {
+    let key = (UserId(4), PostId(2));
+    assert!(!key.matches_pattern(&OrgId(4)));
+    assert!(!key.matches_pattern(&PostId(4)));
+    assert!(!key.matches_pattern(&(UserId(4), OrgId(2))));
+    assert!(!key.matches_pattern(&4u32));
+    assert!(!(4u32, 2u32).matches_pattern(&4u64));
+}
+
+#[test]
+fn structs_are_a_single_segment() {
+    let comment = Comment {
+        post: PostId(2),
+        index: (1, 3),
+    };
+    assert_eq!(comment.segments().len(), 1);
+
+    let key = (UserId(4), comment.clone(), Filter::All);
+    assert!(key.matches_pattern(&(Any, comment.clone())));
+    assert!(key.matches_pattern(&(Any, Any, Filter::All)));
+    assert!(!key.matches_pattern(&(Any, PostId(2))));
+    assert!(!key.matches_pattern(&(
+        Any,
+        Comment {
+            post: PostId(2),
+            index: (1, 4),
+        }
+    )));
+}
+
+type Runs = Rc<RefCell<Vec<(UserId, PostId)>>>;
+
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct GetPost(Captured<Runs>);
+
+impl QueryCapability for GetPost {
+    type Ok = ();
+    type Err = ();
+    type Keys = (UserId, PostId);
+
+    async fn run(&self, keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        self.0.borrow_mut().push(keys.clone());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        Ok(())
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn invalidates_the_queries_matching_a_pattern() {
+    let runs = Runs::default();
+    let mut harness = Harness::new({
+        let runs = runs.clone();
+        move || {
+            for (user, post) in [(1, 1), (1, 2), (2, 1)] {
+                use_query(
+                    Query::new(
+                        (UserId(user), PostId(post)),
+                        GetPost(Captured(runs.clone())),
+                    )
+                    .stale_time(Duration::MAX),
+                );
+            }
+            rsx!()
+        }
+    });
+    harness.run_for(Duration::from_secs(1)).await;
+
+    runs.borrow_mut().clear();
+    harness
+        .run(QueriesStorage::<GetPost>::invalidate_matching(KeyPattern(
+            UserId(1),
+        )))
+        .await;
+    let mut ran = runs.borrow().clone();
+    ran.sort_by_key(|(user, post)| (user.0, post.0));
+    assert_eq!(ran, [(UserId(1), PostId(1)), (UserId(1), PostId(2))]);
+
+    runs.borrow_mut().clear();
+    harness
+        .run(QueriesStorage::<GetPost>::invalidate_matching(KeyPattern(
+            (Any, PostId(1)),
+        )))
+        .await;
+    let mut ran = runs.borrow().clone();
+    ran.sort_by_key(|(user, post)| (user.0, post.0));
+    assert_eq!(ran, [(UserId(1), PostId(1)), (UserId(2), PostId(1))]);
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "testing")]
+
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+/// Takes a second to fetch the name of a user.
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct GetUserName;
+
+impl QueryCapability for GetUserName {
+    type Ok = String;
+    type Err = ();
+    type Keys = u32;
+
+    async fn run(&self, id: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        Ok(format!("User {id}"))
+    }
+}
+
+fn app() -> Element {
+    use_query(Query::new(1, GetUserName)).read();
+    use_query(Query::new(2, GetUserName)).read();
+    rsx!()
+}
+
+fn invalidate(tester: &QueryTester) {
+    tester.in_runtime(|| spawn(QueriesStorage::<GetUserName>::invalidate_all()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn serves_the_mocked_results() {
+    let mut tester = QueryTester::new(app);
+    tester.mock::<GetUserName>(|id| match id {
+        1 => Ok("Alice".to_string()),
+        _ => Err(()),
+    });
+    tester.settle().await;
+
+    assert_eq!(
+        tester.settled::<GetUserName>(&1),
+        Some(Ok("Alice".to_string()))
+    );
+    assert_eq!(tester.settled::<GetUserName>(&2), Some(Err(())));
+}
+
+#[tokio::test(start_paused = true)]
+async fn replaces_and_removes_the_mock() {
+    let mut tester = QueryTester::new(app);
+    tester.mock::<GetUserName>(|_| Ok("Alice".to_string()));
+    tester.settle().await;
+
+    tester.mock::<GetUserName>(|_| Ok("Bob".to_string()));
+    invalidate(&tester);
+    tester.settle().await;
+    assert_eq!(
+        tester.settled::<GetUserName>(&1),
+        Some(Ok("Bob".to_string()))
+    );
+
+    tester.in_runtime(QueriesStorage::<GetUserName>::unmock);
+    invalidate(&tester);
+    tester.settle().await;
+    assert_eq!(
+        tester.settled::<GetUserName>(&1),
+        Some(Ok("User 1".to_string()))
+    );
+    assert_eq!(
+        tester.settled::<GetUserName>(&2),
+        Some(Ok("User 2".to_string()))
+    );
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{Harness, Slot, Slow};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+/// Subscribe to the given query, optionally going offline first.
+fn mount(query: Query<Slow>, online: bool) -> (Harness, Slot<UseQuery<Slow>>) {
+    let slot = Slot::default();
+    let harness = Harness::new({
+        let slot = slot.clone();
+        move || {
+            use_hook(|| OnlineManager::current().set_online(online));
+            let query = use_query(query.clone());
+            query.read();
+            slot.set(query);
+            rsx!()
+        }
+    });
+    (harness, slot)
+}
+
+fn set_online(harness: &Harness, online: bool) {
+    harness.in_app(|| OnlineManager::current().set_online(online));
+}
+
+#[tokio::test(start_paused = true)]
+async fn pauses_while_offline_and_resumes_once_online() {
+    let slow = Slow::new();
+    let (mut harness, query) = mount(Query::new(1, slow.clone()), false);
+    harness.run_for(Duration::from_secs(5)).await;
+    assert!(harness.in_app(|| query.get().peek().state().is_paused()));
+    assert_eq!(slow.calls(), 0);
+
+    set_online(&harness, true);
+    harness.run_for(Duration::from_secs(5)).await;
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(1))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn runs_while_offline_with_the_always_network_mode() {
+    let slow = Slow::new();
+    let (mut harness, query) = mount(
+        Query::new(1, slow.clone()).network_mode(NetworkMode::Always),
+        false,
+    );
+    harness.run_for(Duration::from_secs(5)).await;
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(1))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn refetches_stale_queries_on_reconnect() {
+    let slow = Slow::new();
+    let fresh = Slow::new();
+    let (mut harness, _) = mount(Query::new(1, slow.clone()).refetch_on_reconnect(true), true);
+    let (mut fresh_harness, _) = mount(
+        Query::new(1, fresh.clone())
+            .refetch_on_reconnect(true)
+            .stale_time(Duration::MAX),
+        true,
+    );
+    harness.run_for(Duration::from_secs(5)).await;
+    fresh_harness.run_for(Duration::from_secs(5)).await;
+
+    for harness in [&harness, &fresh_harness] {
+        set_online(harness, false);
+        set_online(harness, true);
+    }
+    harness.run_for(Duration::from_secs(5)).await;
+    fresh_harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(slow.calls(), 2);
+    assert_eq!(fresh.calls(), 1);
+}
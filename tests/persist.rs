@@ -0,0 +1,97 @@
+#![cfg(feature = "persist")]
+
+mod common;
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+use common::{Harness, Slot, Slow};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+/// Keeps the snapshot in memory and counts how many times it was saved.
+#[derive(Clone, Default)]
+struct MemoryPersister {
+    client: Rc<RefCell<Option<PersistedClient>>>,
+    saves: Rc<Cell<u32>>,
+}
+
+impl Persister for MemoryPersister {
+    fn restore(&self) -> Option<PersistedClient> {
+        self.client.borrow().clone()
+    }
+
+    fn persist(&self, client: &PersistedClient) {
+        *self.client.borrow_mut() = Some(client.clone());
+        self.saves.set(self.saves.get() + 1);
+    }
+
+    fn remove(&self) {
+        self.client.borrow_mut().take();
+    }
+}
+
+fn mount(persister: MemoryPersister, slow: Slow) -> (Harness, Slot<UseQuery<Slow>>) {
+    let query = Slot::default();
+    let harness = Harness::new({
+        let query = query.clone();
+        move || {
+            use_persist_query_client(|| {
+                PersistQueryClient::new(persister.clone()).persist::<Slow>()
+            });
+            let value = use_query(Query::new(1, slow.clone()).stale_time(Duration::MAX));
+            value.read();
+            query.set(value);
+            rsx!()
+        }
+    });
+    (harness, query)
+}
+
+#[tokio::test(start_paused = true)]
+async fn saves_only_when_the_cache_changes() {
+    let persister = MemoryPersister::default();
+    let (mut harness, query) = mount(persister.clone(), Slow::new());
+
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(persister.saves.get(), 1);
+
+    // Nothing changed
+    harness.run_for(Duration::from_secs(60)).await;
+    assert_eq!(persister.saves.get(), 1);
+
+    harness.in_app(|| query.get().invalidate());
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(persister.saves.get(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn restores_the_saved_queries() {
+    let persister = MemoryPersister::default();
+    let (mut harness, _) = mount(persister.clone(), Slow::new());
+    harness.run_for(Duration::from_secs(5)).await;
+    drop(harness);
+
+    let slow = Slow::new();
+    let (mut harness, query) = mount(persister.clone(), slow.clone());
+    harness.run_for(Duration::from_secs(5)).await;
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(1))));
+    assert_eq!(slow.calls(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn discards_snapshots_of_other_busters() {
+    let persister = MemoryPersister::default();
+    let (mut harness, _) = mount(persister.clone(), Slow::new());
+    harness.run_for(Duration::from_secs(5)).await;
+    drop(harness);
+
+    persister.client.borrow_mut().as_mut().unwrap().buster = "old".to_string();
+    let slow = Slow::new();
+    let (mut harness, _) = mount(persister.clone(), slow.clone());
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(slow.calls(), 1);
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{Harness, Slot};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+/// Takes a second to double its keys.
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct Double;
+
+impl QueryCapability for Double {
+    type Ok = u32;
+    type Err = ();
+    type Keys = u32;
+
+    async fn run(&self, keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        Ok(keys * 2)
+    }
+}
+
+fn mount(keep_previous_data: bool) -> (Harness, Slot<UseQuery<Double>>, Slot<Signal<u32>>) {
+    let query = Slot::default();
+    let keys = Slot::default();
+    let harness = Harness::new({
+        let query = query.clone();
+        let keys = keys.clone();
+        move || {
+            let current_keys = use_signal(|| 1);
+            keys.set(current_keys);
+            query.set(use_query(
+                Query::new(current_keys(), Double).keep_previous_data(keep_previous_data),
+            ));
+            rsx!()
+        }
+    });
+    (harness, query, keys)
+}
+
+/// The value and where its placeholder comes from, if it is one.
+fn read(
+    harness: &Harness,
+    query: &Slot<UseQuery<Double>>,
+) -> (Option<u32>, Option<QueryPlaceholder>) {
+    harness.in_app(|| {
+        let reader = query.get().peek();
+        let value = reader.state().ok().copied();
+        (value, reader.placeholder())
+    })
+}
+
+#[tokio::test(start_paused = true)]
+async fn keeps_the_value_of_the_previous_keys_while_loading() {
+    let (mut harness, query, keys) = mount(true);
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(read(&harness, &query), (Some(2), None));
+
+    harness.in_app(|| keys.get().set(2));
+    harness.run_for(Duration::from_millis(100)).await;
+    assert_eq!(
+        read(&harness, &query),
+        (Some(2), Some(QueryPlaceholder::PreviousData))
+    );
+    assert!(harness.in_app(|| query.get().peek().state().is_loading()));
+
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(read(&harness, &query), (Some(4), None));
+}
+
+#[tokio::test(start_paused = true)]
+async fn goes_back_to_loading_without_previous_data() {
+    let (mut harness, query, keys) = mount(false);
+    harness.run_for(Duration::from_secs(5)).await;
+
+    harness.in_app(|| keys.get().set(2));
+    harness.run_for(Duration::from_millis(100)).await;
+    assert_eq!(read(&harness, &query), (None, None));
+}
+
+#[tokio::test(start_paused = true)]
+async fn suspends_while_showing_the_previous_data() {
+    let (mut harness, query, keys) = mount(true);
+    harness.run_for(Duration::from_secs(5)).await;
+
+    harness.in_app(|| keys.get().set(2));
+    harness.run_for(Duration::from_millis(100)).await;
+    assert!(harness.in_app(|| query.get().suspend().is_err()));
+
+    harness.run_for(Duration::from_secs(5)).await;
+    assert!(harness.in_app(|| matches!(query.get().suspend(), Ok(Ok(4)))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn tells_the_previous_data_from_the_placeholder_data() {
+    let query = Slot::default();
+    let keys = Slot::default();
+    let mut harness = Harness::new({
+        let query = query.clone();
+        let keys = keys.clone();
+        move || {
+            let current_keys = use_signal(|| 1);
+            keys.set(current_keys);
+            query.set(use_query(
+                Query::new(current_keys(), Double)
+                    .keep_previous_data(true)
+                    .placeholder_data(|_| 0),
+            ));
+            rsx!()
+        }
+    });
+    harness.run_for(Duration::from_millis(100)).await;
+    assert_eq!(
+        read(&harness, &query),
+        (Some(0), Some(QueryPlaceholder::PlaceholderData))
+    );
+
+    harness.run_for(Duration::from_secs(5)).await;
+    harness.in_app(|| keys.get().set(2));
+    harness.run_for(Duration::from_millis(100)).await;
+    assert_eq!(
+        read(&harness, &query),
+        (Some(2), Some(QueryPlaceholder::PreviousData))
+    );
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use common::{Flaky, Harness, Slot};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+fn mount(
+    failures: u32,
+    retry: QueryRetry<Flaky>,
+) -> (Harness, Slot<UseQuery<Flaky>>, Rc<Cell<u32>>) {
+    let calls = Rc::new(Cell::new(0));
+    let slot = Slot::default();
+    let harness = Harness::new({
+        let calls = calls.clone();
+        let slot = slot.clone();
+        move || {
+            let query = use_query(
+                Query::new(failures, Flaky(Captured(calls.clone()))).retry(retry.clone()),
+            );
+            slot.set(query);
+            rsx!()
+        }
+    });
+    (harness, slot, calls)
+}
+
+#[tokio::test(start_paused = true)]
+async fn retries_until_it_succeeds() {
+    let (mut harness, query, calls) = mount(2, QueryRetry::new(3));
+
+    harness.run_for(Duration::from_millis(100)).await;
+    assert_eq!(calls.get(), 1);
+    assert!(harness.in_app(|| query.get().peek().state().is_loading()));
+
+    harness.run_for(Duration::from_secs(10)).await;
+    assert_eq!(calls.get(), 3);
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(2))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn settles_with_the_error_once_out_of_attempts() {
+    let (mut harness, query, calls) = mount(5, QueryRetry::new(2));
+
+    harness.run_for(Duration::from_secs(10)).await;
+    assert_eq!(calls.get(), 3);
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Err(2))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn only_retries_the_errors_accepted_by_retry_if() {
+    let (mut harness, query, calls) =
+        mount(5, QueryRetry::new(3).retry_if(|attempt| *attempt == 0));
+
+    harness.run_for(Duration::from_secs(10)).await;
+    assert_eq!(calls.get(), 2);
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Err(1))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn waits_the_backoff_between_attempts() {
+    let (mut harness, _, calls) = mount(1, QueryRetry::new(1).delay(Duration::from_secs(2)));
+
+    // The backoff is somewhere between half and the full delay
+    harness.run_for(Duration::from_millis(900)).await;
+    assert_eq!(calls.get(), 1);
+    harness.run_for(Duration::from_millis(1200)).await;
+    assert_eq!(calls.get(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn subscribers_with_different_retries_share_the_query() {
+    let calls = Rc::new(Cell::new(0));
+    let mut harness = Harness::new({
+        let calls = calls.clone();
+        move || {
+            use_query(Query::new(0, Flaky(Captured(calls.clone()))).retry(QueryRetry::new(1)));
+            use_query(Query::new(0, Flaky(Captured(calls.clone()))).retry(QueryRetry::new(5)));
+            rsx!()
+        }
+    });
+
+    harness.run_for(Duration::from_secs(1)).await;
+    assert_eq!(calls.get(), 1);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use common::{Harness, Slot};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+/// Takes a second to resolve with its keys and how many times it ran.
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct Versioned(Captured<Rc<Cell<u32>>>);
+
+impl QueryCapability for Versioned {
+    type Ok = (u32, u32);
+    type Err = ();
+    type Keys = u32;
+
+    async fn run(&self, keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        let call = self.0.get() + 1;
+        self.0.set(call);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        Ok((*keys, call))
+    }
+}
+
+struct Mounted {
+    harness: Harness,
+    selected: Slot<Option<u32>>,
+    renders: Rc<Cell<u32>>,
+    keys: Slot<Signal<u32>>,
+}
+
+/// Mount a component that only selects the keys of the query value.
+fn mount() -> Mounted {
+    let selected = Slot::default();
+    let renders = Rc::new(Cell::new(0));
+    let keys = Slot::default();
+    let harness = Harness::new({
+        let selected = selected.clone();
+        let renders = renders.clone();
+        let keys = keys.clone();
+        move || {
+            let current_keys = use_signal(|| 1);
+            keys.set(current_keys);
+            let calls = use_hook(Rc::default);
+            let value = use_query_select(
+                Query::new(current_keys(), Versioned(Captured(calls))),
+                |(keys, _)| *keys,
+            );
+            selected.set(value());
+            renders.set(renders.get() + 1);
+            rsx!()
+        }
+    });
+    Mounted {
+        harness,
+        selected,
+        renders,
+        keys,
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn follows_the_changes_of_the_keys() {
+    let mut mounted = mount();
+    mounted.harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(mounted.selected.get(), Some(1));
+
+    mounted.harness.in_app(|| mounted.keys.get().set(5));
+    mounted.harness.run_for(Duration::from_millis(100)).await;
+    assert_eq!(mounted.selected.get(), None);
+
+    mounted.harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(mounted.selected.get(), Some(5));
+}
+
+#[tokio::test(start_paused = true)]
+async fn skips_the_changes_outside_of_the_selection() {
+    let mut mounted = mount();
+    mounted.harness.run_for(Duration::from_secs(5)).await;
+    let renders = mounted.renders.get();
+
+    // The value changes but its keys don't
+    mounted
+        .harness
+        .in_app(|| QueriesStorage::<Versioned>::set_data(1, (1, 100)));
+    mounted.harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(mounted.selected.get(), Some(1));
+    assert_eq!(mounted.renders.get(), renders);
+
+    mounted
+        .harness
+        .in_app(|| QueriesStorage::<Versioned>::set_data(1, (2, 100)));
+    mounted.harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(mounted.selected.get(), Some(2));
+    assert_eq!(mounted.renders.get(), renders + 1);
+}
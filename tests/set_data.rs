@@ -0,0 +1,95 @@
+mod common;
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+use common::{Harness, Slot, Slow};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+#[tokio::test(start_paused = true)]
+async fn writes_update_the_matching_queries_without_running_them() {
+    let slow = Slow::new();
+    let renders = Rc::new(Cell::new(0));
+    let values = Slot::<(Option<u32>, Option<u32>)>::default();
+    let mut harness = Harness::new({
+        let slow = slow.clone();
+        let renders = renders.clone();
+        let values = values.clone();
+        move || {
+            let first = use_query(Query::new(0, slow.clone()));
+            let second = use_query(Query::new(1, slow.clone()));
+            renders.set(renders.get() + 1);
+            values.set((
+                first.read().state().ok().copied(),
+                second.read().state().ok().copied(),
+            ));
+            rsx!()
+        }
+    });
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(slow.calls(), 2);
+    let (first, second) = values.get();
+    assert!(first.is_some() && second.is_some());
+
+    let renders_before = renders.get();
+    harness.in_app(|| QueriesStorage::<Slow>::set_data(0, 42));
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(values.get(), (Some(42), second));
+    assert!(renders.get() > renders_before);
+
+    harness.in_app(|| QueriesStorage::<Slow>::update_data(0, |value| value.unwrap() + 1));
+    harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(values.get(), (Some(43), second));
+    assert_eq!(slow.calls(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn updates_without_a_value_receive_none() {
+    let (mut harness, query) = common::mount(Query::new(0, Slow::new()));
+    harness.run_for(Duration::from_millis(100)).await;
+
+    let received = Rc::new(Cell::new(Some(0)));
+    harness.in_app(|| {
+        let received = received.clone();
+        QueriesStorage::<Slow>::update_data(0, move |value| {
+            received.set(value.copied());
+            7
+        })
+    });
+    assert_eq!(received.get(), None);
+    assert!(harness.in_app(|| matches!(*query.get().peek().as_settled(), Ok(7))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn writes_seed_the_queries_not_cached_yet() {
+    let slow = Slow::new();
+    // No query of this type is used yet
+    let mut harness = Harness::new(|| rsx!());
+
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let update = |received: &Rc<RefCell<Vec<Option<u32>>>>| {
+        let received = received.clone();
+        move |value: Option<&u32>| {
+            received.borrow_mut().push(value.copied());
+            value.map_or(42, |value| value + 1)
+        }
+    };
+    harness.in_app(|| QueriesStorage::<Slow>::update_data(0, update(&received)));
+    harness.in_app(|| QueriesStorage::<Slow>::update_data(0, update(&received)));
+    assert_eq!(*received.borrow(), [None, Some(42)]);
+
+    let get = GetQuery::new(0, slow.clone())
+        .stale_time(Duration::from_secs(60))
+        .clean_time(Duration::from_secs(60));
+    let reader = harness.run(QueriesStorage::get(get)).await;
+    assert!(matches!(*reader.as_settled(), Ok(43)));
+    assert_eq!(slow.calls(), 0);
+
+    // Once cached, writes go to the entry
+    harness.in_app(|| QueriesStorage::<Slow>::set_data(0, 7));
+    assert!(matches!(*reader.as_settled(), Ok(7)));
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use common::{Harness, Slot};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+/// Takes a second to resolve with its current value.
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct Value(Captured<Rc<Cell<u32>>>);
+
+impl QueryCapability for Value {
+    type Ok = u32;
+    type Err = ();
+    type Keys = ();
+
+    async fn run(&self, _keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        Ok(self.0.get())
+    }
+}
+
+struct Mounted {
+    harness: Harness,
+    query: Slot<UseQuery<Value>>,
+    value: Rc<Cell<u32>>,
+    renders: Rc<Cell<u32>>,
+}
+
+fn mount(structural_sharing: bool) -> Mounted {
+    let query = Slot::default();
+    let value = Rc::new(Cell::new(1));
+    let renders = Rc::new(Cell::new(0));
+    let harness = Harness::new({
+        let query = query.clone();
+        let value = value.clone();
+        let renders = renders.clone();
+        move || {
+            let value = use_query(
+                Query::new((), Value(Captured(value.clone())))
+                    .structural_sharing(structural_sharing),
+            );
+            value.read();
+            query.set(value);
+            renders.set(renders.get() + 1);
+            rsx!()
+        }
+    });
+    Mounted {
+        harness,
+        query,
+        value,
+        renders,
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn refetches_are_loading_without_structural_sharing() {
+    let mut mounted = mount(false);
+    mounted.harness.run_for(Duration::from_secs(5)).await;
+    let renders = mounted.renders.get();
+
+    mounted.harness.in_app(|| mounted.query.get().invalidate());
+    mounted.harness.run_for(Duration::from_millis(100)).await;
+    assert!(mounted
+        .harness
+        .in_app(|| mounted.query.get().peek().state().is_loading()));
+
+    // Loading and settling again, even if the value is the same
+    mounted.harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(mounted.renders.get(), renders + 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn refetches_happen_in_the_background_with_structural_sharing() {
+    let mut mounted = mount(true);
+    mounted.harness.run_for(Duration::from_secs(5)).await;
+    let renders = mounted.renders.get();
+
+    mounted.harness.in_app(|| mounted.query.get().invalidate());
+    mounted.harness.run_for(Duration::from_millis(100)).await;
+    assert!(mounted
+        .harness
+        .in_app(|| matches!(*mounted.query.get().peek().as_settled(), Ok(1))));
+
+    // The value is the same, so there is nothing to re-render
+    mounted.harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(mounted.renders.get(), renders);
+
+    mounted.value.set(2);
+    mounted.harness.in_app(|| mounted.query.get().invalidate());
+    mounted.harness.run_for(Duration::from_secs(5)).await;
+    assert_eq!(mounted.renders.get(), renders + 1);
+    assert!(mounted
+        .harness
+        .in_app(|| matches!(*mounted.query.get().peek().as_settled(), Ok(2))));
+}
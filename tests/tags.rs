@@ -0,0 +1,102 @@
+mod common;
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use common::Harness;
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+type Runs = Rc<RefCell<Vec<(&'static str, u32)>>>;
+
+/// A user, tagged by its id.
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct User(Captured<Runs>);
+
+impl QueryCapability for User {
+    type Ok = ();
+    type Err = ();
+    type Keys = u32;
+
+    async fn run(&self, id: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        self.0.borrow_mut().push(("user", *id));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        Ok(())
+    }
+
+    fn tags(&self, id: &Self::Keys) -> Vec<Tag> {
+        vec![format!("user:{id}").into()]
+    }
+}
+
+/// The posts of a user, tagged by its id and as posts.
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct Posts(Captured<Runs>);
+
+impl QueryCapability for Posts {
+    type Ok = ();
+    type Err = ();
+    type Keys = u32;
+
+    async fn run(&self, id: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        self.0.borrow_mut().push(("posts", *id));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        Ok(())
+    }
+
+    fn tags(&self, id: &Self::Keys) -> Vec<Tag> {
+        vec![format!("user:{id}").into(), "posts".into()]
+    }
+}
+
+fn mount() -> (Harness, Runs) {
+    let runs = Runs::default();
+    let harness = Harness::new({
+        let runs = runs.clone();
+        move || {
+            for id in [1, 2] {
+                use_query(Query::new(id, User(Captured(runs.clone()))).stale_time(Duration::MAX));
+                use_query(Query::new(id, Posts(Captured(runs.clone()))).stale_time(Duration::MAX));
+            }
+            rsx!()
+        }
+    });
+    (harness, runs)
+}
+
+#[tokio::test(start_paused = true)]
+async fn invalidates_the_tagged_queries_of_every_type() {
+    let (mut harness, runs) = mount();
+    harness.run_for(Duration::from_secs(1)).await;
+    runs.borrow_mut().clear();
+
+    harness
+        .run(async { QueryClient::current().invalidate_tags(&["user:1"]).await })
+        .await;
+    let mut ran = runs.borrow().clone();
+    ran.sort();
+    assert_eq!(ran, [("posts", 1), ("user", 1)]);
+
+    runs.borrow_mut().clear();
+    harness
+        .run(async {
+            QueryClient::current()
+                .invalidate_tags(&["posts", "unknown"])
+                .await
+        })
+        .await;
+    let mut ran = runs.borrow().clone();
+    ran.sort();
+    assert_eq!(ran, [("posts", 1), ("posts", 2)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn invalidates_the_tagged_queries_of_a_type() {
+    let (mut harness, runs) = mount();
+    harness.run_for(Duration::from_secs(1)).await;
+    runs.borrow_mut().clear();
+
+    harness
+        .run(QueriesStorage::<User>::invalidate_tags(&["user:2"]))
+        .await;
+    assert_eq!(*runs.borrow(), [("user", 2)]);
+}
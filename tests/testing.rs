@@ -0,0 +1,149 @@
+#![cfg(feature = "testing")]
+
+use std::{cell::RefCell, time::Duration};
+
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct Echo;
+
+impl QueryCapability for Echo {
+    type Ok = u32;
+    type Err = u32;
+    type Keys = u32;
+
+    async fn run(&self, keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        Ok(*keys)
+    }
+}
+
+fn app() -> Element {
+    use_query(Query::new(1, Echo)).read();
+    rsx!()
+}
+
+fn invalidate(tester: &QueryTester) {
+    tester.in_runtime(|| spawn(QueriesStorage::<Echo>::invalidate_matching(1)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn settles_the_queries() {
+    let mut tester = QueryTester::new(app);
+    let probe = tester.probe::<Echo>();
+    tester.settle().await;
+
+    assert_eq!(tester.settled::<Echo>(&1), Some(Ok(1)));
+    assert_eq!(probe.runs(&1), 1);
+    assert_eq!(probe.runs(&2), 0);
+    assert_eq!(probe.total_runs(), 1);
+
+    invalidate(&tester);
+    tester.settle().await;
+    assert_eq!(probe.runs(&1), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn fails_the_probed_queries_until_healed() {
+    let mut tester = QueryTester::new(app);
+    let probe = tester.probe::<Echo>();
+    probe.fail(1, || 7);
+    tester.settle().await;
+    assert_eq!(tester.settled::<Echo>(&1), Some(Err(7)));
+
+    invalidate(&tester);
+    tester.settle().await;
+    assert_eq!(tester.settled::<Echo>(&1), Some(Err(7)));
+
+    probe.heal(&1);
+    invalidate(&tester);
+    tester.settle().await;
+    assert_eq!(tester.settled::<Echo>(&1), Some(Ok(1)));
+    assert_eq!(probe.runs(&1), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn fails_the_probed_queries_once() {
+    let mut tester = QueryTester::new(app);
+    let probe = tester.probe::<Echo>();
+    probe.fail_once(1, || 7);
+    tester.settle().await;
+    assert_eq!(tester.settled::<Echo>(&1), Some(Err(7)));
+
+    invalidate(&tester);
+    tester.settle().await;
+    assert_eq!(tester.settled::<Echo>(&1), Some(Ok(1)));
+}
+
+fn retrying_app() -> Element {
+    use_query(Query::new(1, Echo).retry(QueryRetry::new(1).delay(Duration::from_secs(4)))).read();
+    rsx!()
+}
+
+#[tokio::test(start_paused = true)]
+async fn settles_until_the_queries_wait_for_the_mock_clock() {
+    let mut tester = QueryTester::new(retrying_app);
+    let clock = tester.mock_clock();
+    let probe = tester.probe::<Echo>();
+    probe.fail_once(1, || 7);
+
+    // Waiting for the retry backoff
+    tester.settle().await;
+    assert_eq!(probe.runs(&1), 1);
+    assert_eq!(
+        tester.query_state::<Echo, _>(&1, |state| state.is_loading()),
+        Some(true)
+    );
+
+    clock.advance(Duration::from_secs(4));
+    tester.settle().await;
+    assert_eq!(probe.runs(&1), 2);
+    assert_eq!(tester.settled::<Echo>(&1), Some(Ok(1)));
+}
+
+thread_local! {
+    static SAVED: RefCell<Vec<Result<u32, u32>>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct Save;
+
+impl MutationCapability for Save {
+    type Ok = u32;
+    type Err = u32;
+    type Keys = u32;
+
+    async fn run(&self, keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        Ok(*keys)
+    }
+
+    async fn on_settled(&self, _keys: &Self::Keys, result: &Result<Self::Ok, Self::Err>) {
+        SAVED.with_borrow_mut(|saved| saved.push(*result));
+    }
+}
+
+fn mutating_app() -> Element {
+    let save = use_mutation(Mutation::new(Save));
+    use_hook(|| {
+        save.mutate(1);
+        save.mutate(2);
+    });
+    rsx!()
+}
+
+#[tokio::test(start_paused = true)]
+async fn settles_and_probes_the_mutations() {
+    let mut tester = QueryTester::new(mutating_app);
+    let probe = tester.mutation_probe::<Save>();
+    probe.fail_once(2, || 7);
+    tester.settle().await;
+
+    assert_eq!(probe.runs(&1), 1);
+    assert_eq!(probe.runs(&2), 1);
+    assert_eq!(probe.total_runs(), 2);
+    let mut saved = SAVED.take();
+    saved.sort();
+    assert_eq!(saved, [Ok(1), Err(7)]);
+}
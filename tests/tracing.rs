@@ -0,0 +1,153 @@
+#![cfg(feature = "tracing")]
+
+mod common;
+
+use std::{
+    cell::Cell,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use common::{Harness, Slot, Slow};
+use dioxus::prelude::*;
+use dioxus_query::prelude::*;
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// Records the messages of the events of `dioxus_query`.
+#[derive(Clone, Default)]
+struct Recorder {
+    messages: Arc<Mutex<Vec<String>>>,
+    spans: Arc<AtomicU64>,
+}
+
+impl Recorder {
+    fn messages(&self) -> Vec<String> {
+        self.messages.lock().unwrap().clone()
+    }
+
+    fn position(&self, message: &str) -> usize {
+        self.messages()
+            .iter()
+            .position(|recorded| recorded == message)
+            .unwrap_or_else(|| panic!("{message} was not recorded."))
+    }
+}
+
+struct Message(Option<String>);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = Some(format!("{value:?}"));
+        }
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "dioxus_query"
+    }
+
+    fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(self.spans.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut message = Message(None);
+        event.record(&mut message);
+        if let Some(message) = message.0 {
+            self.messages.lock().unwrap().push(message);
+        }
+    }
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+#[derive(Clone, PartialEq, Hash, Eq)]
+struct Save;
+
+impl MutationCapability for Save {
+    type Ok = ();
+    type Err = ();
+    type Keys = ();
+
+    async fn run(&self, _keys: &Self::Keys) -> Result<Self::Ok, Self::Err> {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        Ok(())
+    }
+}
+
+#[component]
+fn Ticking() -> Element {
+    use_query(
+        Query::new(1, Slow::new())
+            .interval_time(Duration::from_secs(5))
+            .clean_time(Duration::from_secs(1)),
+    );
+    rsx!()
+}
+
+#[tokio::test(start_paused = true)]
+async fn traces_the_lifecycle_of_the_queries() {
+    let recorder = Recorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+
+    let mounted = Rc::new(Cell::new(true));
+    let mut harness = Harness::new({
+        let mounted = mounted.clone();
+        move || {
+            rsx!(if mounted.get() {
+                Ticking {}
+            })
+        }
+    });
+    harness.run_for(Duration::from_secs(7)).await;
+
+    mounted.set(false);
+    harness.rerender();
+    harness.run_for(Duration::from_secs(2)).await;
+
+    assert!(recorder.position("Query mounted") < recorder.position("Fetch started"));
+    assert!(recorder.position("Fetch started") < recorder.position("Fetch finished"));
+    assert!(recorder.position("Fetch finished") < recorder.position("Interval tick"));
+    assert!(recorder.position("Clean task scheduled") < recorder.position("Query evicted"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn traces_the_lifecycle_of_the_mutations() {
+    let recorder = Recorder::default();
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+
+    let mutation = Slot::default();
+    let mut harness = Harness::new({
+        let mutation = mutation.clone();
+        move || {
+            mutation.set(use_mutation(Mutation::new(Save)));
+            rsx!()
+        }
+    });
+    harness
+        .run(async move { mutation.get().mutate_async(()).await })
+        .await;
+
+    assert!(recorder.position("Mutation started") < recorder.position("Mutation settled"));
+    assert!(
+        recorder.position("Mutation settled") < recorder.position("Mutation on_settled finished")
+    );
+    assert!(
+        recorder.position("Mutation on_settled finished") < recorder.position("Mutation finished")
+    );
+}